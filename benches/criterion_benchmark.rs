@@ -56,6 +56,120 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| intersect_all_u128(&u128_bags))
     });
 
+    let u64_empty_heavy_bags: [PrimeBag64<MyElement>; COUNT] =
+        core::array::from_fn(|i| if i % 4 == 0 { u64_bags[i] } else { PrimeBag64::EMPTY });
+    c.bench_function("Intersect empty heavy u64", |b| {
+        b.iter(|| intersect_all_u64(&u64_empty_heavy_bags))
+    });
+
+    let u64_full_bags: [PrimeBag64<MyElement>; COUNT] = core::array::from_fn(|_| {
+        let mut bag = PrimeBag64::EMPTY;
+        while let Some(new_bag) = bag.try_insert(MyElement(rng.gen_range(0..8))) {
+            bag = new_bag;
+        }
+        bag
+    });
+    let u128_full_bags: [PrimeBag128<MyElement>; COUNT] = core::array::from_fn(|_| {
+        let mut bag = PrimeBag128::EMPTY;
+        while let Some(new_bag) = bag.try_insert(MyElement(rng.gen_range(0..8))) {
+            bag = new_bag;
+        }
+        bag
+    });
+
+    c.bench_function("Repeated next back u64", |b| {
+        b.iter(|| repeated_next_back_all_u64(&u64_full_bags))
+    });
+    c.bench_function("Nth back u64", |b| {
+        b.iter(|| nth_back_all_u64(&u64_full_bags))
+    });
+    c.bench_function("Repeated next back u128", |b| {
+        b.iter(|| repeated_next_back_all_u128(&u128_full_bags))
+    });
+    c.bench_function("Nth back u128", |b| {
+        b.iter(|| nth_back_all_u128(&u128_full_bags))
+    });
+
+    let small_indices: [usize; COUNT] = core::array::from_fn(|_| rng.gen_range(0..4));
+    c.bench_function("Try insert small indices u64", |b| {
+        b.iter(|| try_insert_all_u64(&small_indices))
+    });
+    c.bench_function("Try insert small indices with match u64", |b| {
+        b.iter(|| try_insert_small_all_u64(&small_indices))
+    });
+
+    let racks: [PrimeBag64<Letter>; COUNT] = core::array::from_fn(|_| {
+        let mut bag = PrimeBag64::EMPTY;
+        while let Some(new_bag) = bag.try_insert(Letter::from_prime_index(rng.gen_range(0..8))) {
+            bag = new_bag;
+        }
+        bag
+    });
+    c.bench_function("Score racks with into_iter", |b| {
+        b.iter(|| score_racks_into_iter(&racks))
+    });
+    c.bench_function("Score racks with into_index_iter", |b| {
+        b.iter(|| score_racks_into_index_iter(&racks))
+    });
+
+    c.bench_function("Superset pairs u64", |b| {
+        b.iter(|| superset_pairs_u64(&u64_bags))
+    });
+    c.bench_function("Superset pairs u128", |b| {
+        b.iter(|| superset_pairs_u128(&u128_bags))
+    });
+
+    c.bench_function("Count supersets u8", |b| {
+        b.iter(|| PrimeBag8::from_inner(NonZeroU8::new(6).unwrap()).count_supersets(&u8_bags))
+    });
+    c.bench_function("Count supersets naive u8", |b| {
+        b.iter(|| count_supersets_naive_u8(&u8_bags))
+    });
+    c.bench_function("Count supersets u16", |b| {
+        b.iter(|| PrimeBag16::from_inner(NonZeroU16::new(6).unwrap()).count_supersets(&u16_bags))
+    });
+    c.bench_function("Count supersets naive u16", |b| {
+        b.iter(|| count_supersets_naive_u16(&u16_bags))
+    });
+    c.bench_function("Count supersets u32", |b| {
+        b.iter(|| PrimeBag32::from_inner(NonZeroU32::new(6).unwrap()).count_supersets(&u32_bags))
+    });
+    c.bench_function("Count supersets naive u32", |b| {
+        b.iter(|| count_supersets_naive_u32(&u32_bags))
+    });
+
+    c.bench_function("Intersection of u8", |b| {
+        b.iter(|| PrimeBag8::intersection_of(&u8_bags))
+    });
+    c.bench_function("Intersection of u16", |b| {
+        b.iter(|| PrimeBag16::intersection_of(&u16_bags))
+    });
+    c.bench_function("Intersection of u32", |b| {
+        b.iter(|| PrimeBag32::intersection_of(&u32_bags))
+    });
+    c.bench_function("Intersection of u64", |b| {
+        b.iter(|| PrimeBag64::intersection_of(&u64_bags))
+    });
+    c.bench_function("Intersection of u128", |b| {
+        b.iter(|| PrimeBag128::intersection_of(&u128_bags))
+    });
+
+    c.bench_function("Union of u8", |b| {
+        b.iter(|| PrimeBag8::try_union_of(&u8_bags))
+    });
+    c.bench_function("Union of u16", |b| {
+        b.iter(|| PrimeBag16::try_union_of(&u16_bags))
+    });
+    c.bench_function("Union of u32", |b| {
+        b.iter(|| PrimeBag32::try_union_of(&u32_bags))
+    });
+    c.bench_function("Union of u64", |b| {
+        b.iter(|| PrimeBag64::try_union_of(&u64_bags))
+    });
+    c.bench_function("Union of u128", |b| {
+        b.iter(|| PrimeBag128::try_union_of(&u128_bags))
+    });
+
     c.bench_function("Union u8", |b| b.iter(|| union_all_u8(&u8_bags)));
     c.bench_function("Union u16", |b| b.iter(|| union_all_u16(&u16_bags)));
     c.bench_function("Union u32", |b| b.iter(|| union_all_u32(&u32_bags)));
@@ -72,7 +186,7 @@ macro_rules! intersect_all {
                 let right = &bags[x + 1];
 
                 let intersection = left.intersection(right);
-                let inner = intersection.into_inner().get();
+                let inner = intersection.into_inner_primitive();
                 total = total.wrapping_add(inner);
             }
             total
@@ -89,7 +203,7 @@ macro_rules! union_all {
                 let right = &bags[x + 1];
 
                 let union1 = left.try_union(right).unwrap_or_default();
-                let inner = union1.into_inner().get();
+                let inner = union1.into_inner_primitive();
                 total = total.wrapping_add(inner);
             }
             total
@@ -97,6 +211,151 @@ macro_rules! union_all {
     };
 }
 
+// `Rev::nth` calls `nth_back` so call `next_back` repeatedly to compare against
+macro_rules! repeated_next_back_all {
+    ($name: ident, $bag: ty) => {
+        fn $name(bags: &[$bag]) -> usize {
+            let mut total: usize = 0;
+            for bag in bags {
+                let mut iter = bag.into_iter();
+                for _ in 0..8 {
+                    let _ = iter.next_back();
+                }
+                if let Some(e) = iter.next_back() {
+                    total = total.wrapping_add(e.0);
+                }
+            }
+            total
+        }
+    };
+}
+
+macro_rules! nth_back_all {
+    ($name: ident, $bag: ty) => {
+        fn $name(bags: &[$bag]) -> usize {
+            let mut total: usize = 0;
+            for bag in bags {
+                if let Some(e) = bag.into_iter().nth_back(8) {
+                    total = total.wrapping_add(e.0);
+                }
+            }
+            total
+        }
+    };
+}
+
+fn try_insert_all_u64(indices: &[usize]) -> u64 {
+    let mut total: u64 = 0;
+    let mut bag = PrimeBag64::<MyElement>::EMPTY;
+    for index in indices {
+        bag = bag
+            .try_insert(MyElement(*index))
+            .unwrap_or(PrimeBag64::EMPTY);
+        total = total.wrapping_add(bag.into_inner_primitive());
+    }
+    total
+}
+
+/// Matches the primes for small indices directly instead of looking them up in the table of primes
+/// This is no faster than `try_insert` so `try_insert` does not use it
+fn try_insert_small(bag: PrimeBag64<MyElement>, index: usize) -> Option<PrimeBag64<MyElement>> {
+    let prime = match index {
+        0 => 2,
+        1 => 3,
+        2 => 5,
+        3 => 7,
+        _ => return bag.try_insert(MyElement(index)),
+    };
+    let inner = bag.into_inner().checked_mul(NonZeroU64::new(prime)?)?;
+    Some(PrimeBag64::from_inner(inner))
+}
+
+fn try_insert_small_all_u64(indices: &[usize]) -> u64 {
+    let mut total: u64 = 0;
+    let mut bag = PrimeBag64::<MyElement>::EMPTY;
+    for index in indices {
+        bag = try_insert_small(bag, *index).unwrap_or(PrimeBag64::EMPTY);
+        total = total.wrapping_add(bag.into_inner_primitive());
+    }
+    total
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Letter {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl_prime_bag_element!(Letter { E = 0, A = 1, D = 2, C = 3, H = 4, B = 5, F = 6, G = 7 });
+
+const LETTER_SCORES: [u32; 8] = [1, 1, 2, 3, 4, 3, 4, 2];
+
+impl Letter {
+    fn score(self) -> u32 {
+        match self {
+            Letter::A | Letter::E => 1,
+            Letter::D | Letter::G => 2,
+            Letter::B | Letter::C => 3,
+            Letter::F | Letter::H => 4,
+        }
+    }
+}
+
+fn score_racks_into_iter(racks: &[PrimeBag64<Letter>]) -> u32 {
+    racks
+        .iter()
+        .map(|rack| rack.into_iter().map(Letter::score).sum::<u32>())
+        .sum()
+}
+
+fn score_racks_into_index_iter(racks: &[PrimeBag64<Letter>]) -> u32 {
+    racks
+        .iter()
+        .map(|rack| {
+            rack.into_index_iter()
+                .map(|i| LETTER_SCORES[i])
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+macro_rules! superset_pairs {
+    ($name: ident, $bag: ty) => {
+        fn $name<T: PrimeBagElement>(bags: &[$bag]) -> usize {
+            let mut total: usize = 0;
+            for left in bags {
+                for right in bags {
+                    if left.is_superset(right) {
+                        total += 1;
+                    }
+                }
+            }
+            total
+        }
+    };
+}
+
+macro_rules! count_supersets_naive {
+    ($name: ident, $bag: ty, $nonzero: ty ) => {
+        fn $name<T: PrimeBagElement>(bags: &[$bag]) -> usize {
+            let word = <$bag>::from_inner(<$nonzero>::new(6).unwrap());
+            let mut total: usize = 0;
+            for bag in bags {
+                if bag.is_superset(&word) {
+                    total += 1;
+                }
+            }
+            total
+        }
+    };
+}
+
 macro_rules! count_2_3s {
     ($name: ident, $bag: ty, $inner: ty ) => {
         fn $name(bags: &[$bag]) -> (usize, usize) {
@@ -126,6 +385,19 @@ union_all!(union_all_u32, PrimeBag32<T>, u32);
 union_all!(union_all_u64, PrimeBag64<T>, u64);
 union_all!(union_all_u128, PrimeBag128<T>, u128);
 
+repeated_next_back_all!(repeated_next_back_all_u64, PrimeBag64<MyElement>);
+repeated_next_back_all!(repeated_next_back_all_u128, PrimeBag128<MyElement>);
+
+nth_back_all!(nth_back_all_u64, PrimeBag64<MyElement>);
+nth_back_all!(nth_back_all_u128, PrimeBag128<MyElement>);
+
+superset_pairs!(superset_pairs_u64, PrimeBag64<T>);
+superset_pairs!(superset_pairs_u128, PrimeBag128<T>);
+
+count_supersets_naive!(count_supersets_naive_u8, PrimeBag8<T>, NonZeroU8);
+count_supersets_naive!(count_supersets_naive_u16, PrimeBag16<T>, NonZeroU16);
+count_supersets_naive!(count_supersets_naive_u32, PrimeBag32<T>, NonZeroU32);
+
 count_2_3s!(count_2_3s_u8, PrimeBag8<MyElement>, u8);
 count_2_3s!(count_2_3s_u16, PrimeBag16<MyElement>, u16);
 count_2_3s!(count_2_3s_u32, PrimeBag32<MyElement>, u32);
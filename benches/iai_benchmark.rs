@@ -46,7 +46,7 @@ macro_rules! intersect_all {
                 let right = &bags[x + 1];
 
                 let intersection = left.intersection(right);
-                let inner = intersection.into_inner().get();
+                let inner = intersection.into_inner_primitive();
                 total = total.wrapping_add(inner);
             }
             total
@@ -65,7 +65,7 @@ macro_rules! union_all {
                 let right = &bags[x + 1];
 
                 let union1 = left.try_union(right).unwrap_or_default();
-                let inner = union1.into_inner().get();
+                let inner = union1.into_inner_primitive();
                 total = total.wrapping_add(inner);
             }
             total
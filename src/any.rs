@@ -0,0 +1,210 @@
+use core::fmt::Debug;
+use core::hash::Hash;
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
+use crate::{PrimeBag128, PrimeBag16, PrimeBag32, PrimeBag64, PrimeBag8, PrimeBagElement};
+
+/// A bag stored using the smallest backing integer which can hold its contents
+pub enum AnyPrimeBag<E> {
+    /// A bag backed by a `u8`
+    U8(PrimeBag8<E>),
+    /// A bag backed by a `u16`
+    U16(PrimeBag16<E>),
+    /// A bag backed by a `u32`
+    U32(PrimeBag32<E>),
+    /// A bag backed by a `u64`
+    U64(PrimeBag64<E>),
+    /// A bag backed by a `u128`
+    U128(PrimeBag128<E>),
+}
+
+impl<E> AnyPrimeBag<E> {
+    /// Convert this bag to a `PrimeBag128` containing the same elements.
+    #[inline]
+    #[must_use]
+    pub fn widen(self) -> PrimeBag128<E> {
+        match self {
+            AnyPrimeBag::U8(bag) => bag.widen(),
+            AnyPrimeBag::U16(bag) => bag.widen(),
+            AnyPrimeBag::U32(bag) => bag.widen(),
+            AnyPrimeBag::U64(bag) => bag.widen(),
+            AnyPrimeBag::U128(bag) => bag,
+        }
+    }
+}
+
+impl<E: PrimeBagElement> AnyPrimeBag<E> {
+    /// Try to create a bag from an iterator of values, using the smallest backing integer which can hold them.
+    /// Returns `None` if the values would not fit even in a `PrimeBag128`.
+    #[must_use]
+    pub fn build_smallest<I: IntoIterator<Item = E>>(iter: I) -> Option<Self> {
+        PrimeBag128::try_from_iter(iter).map(PrimeBag128::shrink)
+    }
+
+    /// Returns the number of instances of `value` in the bag.
+    #[must_use]
+    pub fn count_instances(&self, value: E) -> usize {
+        match self {
+            AnyPrimeBag::U8(bag) => bag.count_instances(value),
+            AnyPrimeBag::U16(bag) => bag.count_instances(value),
+            AnyPrimeBag::U32(bag) => bag.count_instances(value),
+            AnyPrimeBag::U64(bag) => bag.count_instances(value),
+            AnyPrimeBag::U128(bag) => bag.count_instances(value),
+        }
+    }
+
+    /// Returns whether the bag contains a particular `value`.
+    #[must_use]
+    pub fn contains(&self, value: E) -> bool {
+        match self {
+            AnyPrimeBag::U8(bag) => bag.contains(value),
+            AnyPrimeBag::U16(bag) => bag.contains(value),
+            AnyPrimeBag::U32(bag) => bag.contains(value),
+            AnyPrimeBag::U64(bag) => bag.contains(value),
+            AnyPrimeBag::U128(bag) => bag.contains(value),
+        }
+    }
+}
+
+impl<E> AnyPrimeBag<E> {
+    /// Returns the number of elements in the bag
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        match self {
+            AnyPrimeBag::U8(bag) => bag.count(),
+            AnyPrimeBag::U16(bag) => bag.count(),
+            AnyPrimeBag::U32(bag) => bag.count(),
+            AnyPrimeBag::U64(bag) => bag.count(),
+            AnyPrimeBag::U128(bag) => bag.count(),
+        }
+    }
+
+    /// Returns whether the bag is empty
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        match self {
+            AnyPrimeBag::U8(bag) => bag.is_empty(),
+            AnyPrimeBag::U16(bag) => bag.is_empty(),
+            AnyPrimeBag::U32(bag) => bag.is_empty(),
+            AnyPrimeBag::U64(bag) => bag.is_empty(),
+            AnyPrimeBag::U128(bag) => bag.is_empty(),
+        }
+    }
+}
+
+impl<E> From<PrimeBag128<E>> for AnyPrimeBag<E> {
+    fn from(value: PrimeBag128<E>) -> Self {
+        let inner = value.into_inner();
+
+        if let Ok(inner) = NonZeroU8::try_from(inner) {
+            AnyPrimeBag::U8(PrimeBag8::from_inner(inner))
+        } else if let Ok(inner) = NonZeroU16::try_from(inner) {
+            AnyPrimeBag::U16(PrimeBag16::from_inner(inner))
+        } else if let Ok(inner) = NonZeroU32::try_from(inner) {
+            AnyPrimeBag::U32(PrimeBag32::from_inner(inner))
+        } else if let Ok(inner) = NonZeroU64::try_from(inner) {
+            AnyPrimeBag::U64(PrimeBag64::from_inner(inner))
+        } else {
+            AnyPrimeBag::U128(value)
+        }
+    }
+}
+
+impl<E> PartialEq for AnyPrimeBag<E> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AnyPrimeBag::U8(a), AnyPrimeBag::U8(b)) => a == b,
+            (AnyPrimeBag::U16(a), AnyPrimeBag::U16(b)) => a == b,
+            (AnyPrimeBag::U32(a), AnyPrimeBag::U32(b)) => a == b,
+            (AnyPrimeBag::U64(a), AnyPrimeBag::U64(b)) => a == b,
+            (AnyPrimeBag::U128(a), AnyPrimeBag::U128(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<E> Eq for AnyPrimeBag<E> {}
+
+impl<E> Hash for AnyPrimeBag<E> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            AnyPrimeBag::U8(bag) => bag.hash(state),
+            AnyPrimeBag::U16(bag) => bag.hash(state),
+            AnyPrimeBag::U32(bag) => bag.hash(state),
+            AnyPrimeBag::U64(bag) => bag.hash(state),
+            AnyPrimeBag::U128(bag) => bag.hash(state),
+        }
+    }
+}
+
+impl<E> Debug for AnyPrimeBag<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AnyPrimeBag::U8(bag) => f.debug_tuple("U8").field(bag).finish(),
+            AnyPrimeBag::U16(bag) => f.debug_tuple("U16").field(bag).finish(),
+            AnyPrimeBag::U32(bag) => f.debug_tuple("U32").field(bag).finish(),
+            AnyPrimeBag::U64(bag) => f.debug_tuple("U64").field(bag).finish(),
+            AnyPrimeBag::U128(bag) => f.debug_tuple("U128").field(bag).finish(),
+        }
+    }
+}
+
+impl<E> Copy for AnyPrimeBag<E> {}
+
+impl<E> Clone for AnyPrimeBag<E> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shrink() {
+        let b8 = PrimeBag128::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        let b16 = PrimeBag128::<usize>::try_from_iter([0, 1, 2, 3, 4]).unwrap();
+        let b32 = PrimeBag128::<usize>::try_from_iter([0, 1, 2, 3, 4, 5, 6]).unwrap();
+        let b64 = PrimeBag128::<usize>::try_from_iter([0, 1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        let b128 = PrimeBag128::<usize>::try_from_iter(0..16).unwrap();
+
+        assert!(matches!(b8.shrink(), AnyPrimeBag::U8(_)));
+        assert!(matches!(b16.shrink(), AnyPrimeBag::U16(_)));
+        assert!(matches!(b32.shrink(), AnyPrimeBag::U32(_)));
+        assert!(matches!(b64.shrink(), AnyPrimeBag::U64(_)));
+        assert!(matches!(b128.shrink(), AnyPrimeBag::U128(_)));
+
+        assert!(matches!(
+            PrimeBag64::<usize>::EMPTY.shrink(),
+            AnyPrimeBag::U8(_)
+        ));
+
+        for bag in [b8, b16, b32, b64, b128] {
+            assert_eq!(bag.shrink().widen(), bag);
+        }
+    }
+
+    #[test]
+    fn test_build_smallest() {
+        let bag = AnyPrimeBag::build_smallest([0, 1, 2]).unwrap();
+        assert!(matches!(bag, AnyPrimeBag::U8(_)));
+        assert_eq!(bag.count(), 3);
+        assert_eq!(bag.count_instances(1), 1);
+        assert!(bag.contains(2));
+        assert!(!bag.contains(3));
+
+        let bag = AnyPrimeBag::build_smallest([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 9]).unwrap();
+        assert!(matches!(bag, AnyPrimeBag::U64(_)));
+        assert_eq!(bag.count_instances(9), 2);
+        assert_eq!(bag.count(), 11);
+
+        let bag = AnyPrimeBag::<usize>::build_smallest([]).unwrap();
+        assert!(matches!(bag, AnyPrimeBag::U8(_)));
+        assert!(bag.is_empty());
+
+        assert_eq!(AnyPrimeBag::build_smallest(0..32), None); //The bag created would be too big
+    }
+}
@@ -0,0 +1,145 @@
+use core::marker::PhantomData;
+use core::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
+use crate::helpers::{Helpers128, Helpers16, Helpers32, Helpers64, Helpers8};
+use crate::{PrimeBag128, PrimeBag16, PrimeBag32, PrimeBag64, PrimeBag8, PrimeBagElement};
+
+macro_rules! prime_bag_builder {
+    ($builder_x: ident, $bag_x: ident, $helpers_x: ty, $nonzero_ux: ty) => {
+        /// Builds a bag by inserting elements one at a time through a mutable reference.
+        /// Failures are remembered and only reported by `finish`, so there is no need to check the result of each insertion.
+        /// Prefer `try_extend` or `try_from_iter` when the elements are available as an iterator.
+        /// Use this when elements are inserted from loops with branches or from several places in your code.
+        /// Create this with `builder` or `Default`
+        #[derive(Debug)]
+        pub struct $builder_x<E> {
+            inner: Option<$nonzero_ux>,
+            phantom: PhantomData<E>,
+        }
+
+        impl<E> Default for $builder_x<E> {
+            fn default() -> Self {
+                Self {
+                    inner: Some(<$helpers_x>::ONE),
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<E> Clone for $builder_x<E> {
+            fn clone(&self) -> Self {
+                Self {
+                    inner: self.inner,
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<E> $bag_x<E> {
+            /// Create a builder which starts with the elements of this bag.
+            #[must_use]
+            pub const fn builder(&self) -> $builder_x<E> {
+                $builder_x {
+                    inner: Some(self.0),
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<E> $builder_x<E> {
+            /// Returns the bag that was built.
+            /// Returns `None` if any insertion made the bag too large or used a prime index which is out of range.
+            #[inline]
+            #[must_use]
+            pub const fn finish(&self) -> Option<$bag_x<E>> {
+                match self.inner {
+                    Some(inner) => Some($bag_x(inner, PhantomData)),
+                    None => None,
+                }
+            }
+
+            /// Returns whether any insertion has failed.
+            /// Once an insertion has failed, further insertions are ignored and `finish` will return `None`.
+            #[inline]
+            #[must_use]
+            pub const fn has_failed(&self) -> bool {
+                self.inner.is_none()
+            }
+        }
+
+        impl<E: PrimeBagElement> $builder_x<E> {
+            /// Insert `value` into the bag being built.
+            #[inline]
+            pub fn insert(&mut self, value: E) -> &mut Self {
+                self.inner = self.inner.and_then(|inner| {
+                    let p = <$helpers_x>::get_prime(value.to_prime_index())?;
+                    inner.checked_mul(p)
+                });
+                self
+            }
+
+            /// Insert `value` into the bag being built `count` times.
+            #[inline]
+            pub fn insert_many(&mut self, value: E, count: u32) -> &mut Self {
+                self.inner = self.inner.and_then(|inner| {
+                    let p = <$helpers_x>::get_prime(value.to_prime_index())?;
+                    inner.checked_mul(p.checked_pow(count)?)
+                });
+                self
+            }
+        }
+    };
+}
+
+prime_bag_builder!(PrimeBagBuilder8, PrimeBag8, Helpers8, NonZeroU8);
+prime_bag_builder!(PrimeBagBuilder16, PrimeBag16, Helpers16, NonZeroU16);
+prime_bag_builder!(PrimeBagBuilder32, PrimeBag32, Helpers32, NonZeroU32);
+prime_bag_builder!(PrimeBagBuilder64, PrimeBag64, Helpers64, NonZeroU64);
+prime_bag_builder!(PrimeBagBuilder128, PrimeBag128, Helpers128, NonZeroU128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let mut builder = PrimeBagBuilder16::<usize>::default();
+        for i in 0..4 {
+            if i % 2 == 0 {
+                builder.insert(i);
+            } else {
+                builder.insert_many(i, 2);
+            }
+        }
+        assert!(!builder.has_failed());
+        let expected = PrimeBag16::<usize>::try_from_iter([0, 1, 1, 2, 3, 3]).unwrap();
+        assert_eq!(builder.finish(), Some(expected));
+
+        let bag = PrimeBag16::<usize>::try_from_iter([4]).unwrap();
+        let mut builder = bag.builder();
+        builder.insert(0).insert_many(1, 0);
+        assert_eq!(builder.finish(), PrimeBag16::<usize>::try_from_iter([0, 4]));
+
+        assert_eq!(
+            PrimeBagBuilder8::<usize>::default().finish(),
+            Some(PrimeBag8::EMPTY)
+        );
+    }
+
+    #[test]
+    fn test_builder_failure() {
+        let mut builder = PrimeBagBuilder16::<usize>::default();
+        builder.insert(1000);
+        assert!(builder.has_failed());
+        builder.insert(0);
+        assert_eq!(builder.finish(), None); //The index is out of range
+
+        let mut builder = PrimeBagBuilder8::<usize>::default();
+        builder.insert_many(0, 7);
+        assert_eq!(builder.finish(), PrimeBag8::try_from_iter([0; 7]));
+        builder.insert(0);
+        assert_eq!(builder.finish(), None); //The bag created would be too big
+        builder.insert_many(0, 0);
+        assert_eq!(builder.finish(), None);
+    }
+}
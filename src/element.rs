@@ -0,0 +1,27 @@
+use crate::PrimeBagElement;
+
+#[cfg(feature = "common-elements")]
+pub(crate) mod common;
+
+/// An element which is just its prime index.
+/// This is useful for examples and quick experiments where defining an element type would be boilerplate.
+///
+/// ```rust
+/// use prime_bag::*;
+///
+/// let bag = PrimeBagIndices16::try_from_iter([0, 1, 1].map(IndexElement)).unwrap();
+/// assert_eq!(bag.count_instances(IndexElement(1)), 2);
+/// assert_eq!(bag.into_iter().map(|e| e.0).collect::<Vec<_>>(), [0, 1, 1]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct IndexElement(pub usize);
+
+impl PrimeBagElement for IndexElement {
+    fn to_prime_index(&self) -> usize {
+        self.0
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        Self(value)
+    }
+}
@@ -0,0 +1,286 @@
+use core::cmp::Ordering;
+
+use crate::PrimeBagElement;
+
+/// An element for domains which number their elements from one.
+/// The value `n` has prime index `n - 1` so no prime index is wasted on zero.
+///
+/// The value `0` is not valid. It is mapped to an out of range prime index so it can never be inserted into a bag.
+///
+/// ```rust
+/// use prime_bag::*;
+///
+/// let bag = PrimeBag16::try_from_iter([1, 2, 2].map(OneBased)).unwrap();
+/// assert_eq!(bag.count_instances(OneBased(2)), 2);
+/// assert_eq!(bag.into_inner_primitive(), 2 * 3 * 3);
+/// assert_eq!(bag.try_insert(OneBased(0)), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OneBased(pub usize);
+
+impl PrimeBagElement for OneBased {
+    fn to_prime_index(&self) -> usize {
+        self.0.checked_sub(1).unwrap_or(usize::MAX)
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        Self(value.saturating_add(1))
+    }
+}
+
+/// An element for small alphabets with values in the range `0..16`.
+/// The value is used as the prime index.
+///
+/// Values of `16` or more are not valid. They are mapped to an out of range prime index so they can never be inserted into a bag.
+///
+/// ```rust
+/// use prime_bag::*;
+///
+/// let bag = PrimeBag32::try_from_iter([0, 3, 3].map(Nibble)).unwrap();
+/// assert_eq!(bag.count_instances(Nibble(3)), 2);
+/// assert_eq!(bag.try_insert(Nibble(16)), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Nibble(pub u8);
+
+impl Nibble {
+    /// Create a nibble, returning `None` if `value` is not in the range `0..16`
+    #[must_use]
+    pub const fn new(value: u8) -> Option<Self> {
+        if value < 16 {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+}
+
+impl PrimeBagElement for Nibble {
+    fn to_prime_index(&self) -> usize {
+        if self.0 < 16 {
+            self.0 as usize
+        } else {
+            usize::MAX
+        }
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        // Only values in the range 0..16 are produced by `to_prime_index`
+        #[allow(clippy::cast_possible_truncation)]
+        Self(value.min(15) as u8)
+    }
+}
+
+impl PrimeBagElement for Ordering {
+    fn to_prime_index(&self) -> usize {
+        match self {
+            Ordering::Less => 0,
+            Ordering::Equal => 1,
+            Ordering::Greater => 2,
+        }
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        match value {
+            0 => Ordering::Less,
+            1 => Ordering::Equal,
+            _ => Ordering::Greater,
+        }
+    }
+}
+
+impl PrimeBagElement for bool {
+    fn to_prime_index(&self) -> usize {
+        usize::from(*self)
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        value != 0
+    }
+}
+
+/// `None` has prime index 0 and `Some(t)` has the prime index of `t` plus one.
+/// So an `Option<T>` uses one more prime index than `T` does.
+impl<T: PrimeBagElement> PrimeBagElement for Option<T> {
+    fn to_prime_index(&self) -> usize {
+        match self {
+            None => 0,
+            Some(t) => t.to_prime_index().saturating_add(1),
+        }
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        match value {
+            0 => None,
+            _ => Some(T::from_prime_index(value - 1)),
+        }
+    }
+}
+
+/// Tuples are mapped to prime indices using the Cantor pairing function.
+/// The prime index grows with the square of the prime indices of the components,
+/// so only pairs whose prime indices sum to at most 6 (or 21 with the `primes256` feature) are guaranteed to fit.
+impl<A: PrimeBagElement, B: PrimeBagElement> PrimeBagElement for (A, B) {
+    fn to_prime_index(&self) -> usize {
+        cantor_pair(self.0.to_prime_index(), self.1.to_prime_index())
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        let (a, b) = cantor_unpair(value);
+        (A::from_prime_index(a), B::from_prime_index(b))
+    }
+}
+
+/// Triples are mapped to prime indices by pairing the first component with the pair of the other two.
+/// The prime index grows very quickly so only triples of small prime indices will fit.
+impl<A: PrimeBagElement, B: PrimeBagElement, C: PrimeBagElement> PrimeBagElement for (A, B, C) {
+    fn to_prime_index(&self) -> usize {
+        cantor_pair(
+            self.0.to_prime_index(),
+            cantor_pair(self.1.to_prime_index(), self.2.to_prime_index()),
+        )
+    }
+
+    fn from_prime_index(value: usize) -> Self {
+        let (a, bc) = cantor_unpair(value);
+        let (b, c) = cantor_unpair(bc);
+        (
+            A::from_prime_index(a),
+            B::from_prime_index(b),
+            C::from_prime_index(c),
+        )
+    }
+}
+
+/// Bijectively maps a pair of indices to a single index
+/// Saturates at `usize::MAX` (which is far outside the range of valid prime indices)
+fn cantor_pair(a: usize, b: usize) -> usize {
+    let sum = a.saturating_add(b);
+    let triangle = if sum.is_multiple_of(2) {
+        (sum / 2).saturating_mul(sum.saturating_add(1))
+    } else {
+        sum.saturating_mul(sum / 2 + 1)
+    };
+    triangle.saturating_add(b)
+}
+
+/// The inverse of `cantor_pair`
+fn cantor_unpair(value: usize) -> (usize, usize) {
+    let value = value as u128;
+    let sum = ((8 * value + 1).isqrt() - 1) / 2;
+    let b = value - (sum * (sum + 1) / 2);
+    let a = sum - b;
+    // a and b are both at most value so this cannot truncate
+    #[allow(clippy::cast_possible_truncation)]
+    (a as usize, b as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Ordering;
+
+    use super::{cantor_pair, cantor_unpair, Nibble, OneBased};
+    use crate::{PrimeBag16, PrimeBag32, PrimeBagElement};
+
+    #[test]
+    fn test_ordering_round_trip() {
+        let elements = [
+            Ordering::Less,
+            Ordering::Equal,
+            Ordering::Equal,
+            Ordering::Greater,
+            Ordering::Greater,
+            Ordering::Greater,
+        ];
+        let bag = PrimeBag16::<Ordering>::try_from_iter(elements).unwrap();
+
+        assert_eq!(bag.count_instances(Ordering::Equal), 2);
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+    }
+
+    #[test]
+    fn test_option_bool_round_trip() {
+        let elements = [None, None, Some(false), Some(true), Some(true)];
+        let bag = PrimeBag16::<Option<bool>>::try_from_iter(elements).unwrap();
+
+        assert_eq!(bag.count_instances(None), 2);
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+    }
+
+    #[test]
+    fn test_option_round_trip() {
+        let elements = [None, Some(Ordering::Less), Some(Ordering::Greater)];
+        let bag = PrimeBag16::<Option<Ordering>>::try_from_iter(elements).unwrap();
+
+        assert_eq!(bag.count_instances(None), 1);
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+    }
+
+    #[test]
+    fn test_tuple_round_trip() {
+        let elements = [
+            (false, Ordering::Less),
+            (true, Ordering::Equal),
+            (false, Ordering::Greater),
+        ];
+        let bag = PrimeBag16::<(bool, Ordering)>::try_from_iter(elements).unwrap();
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+
+        let elements = [(true, false, false), (false, true, false)];
+        let bag = PrimeBag32::<(bool, bool, bool)>::try_from_iter(elements).unwrap();
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+    }
+
+    #[test]
+    fn test_one_based_round_trip() {
+        let elements = [1, 1, 2, 5].map(OneBased);
+        let bag = PrimeBag16::<OneBased>::try_from_iter(elements).unwrap();
+
+        assert_eq!(bag.count_instances(OneBased(1)), 2);
+        assert!(!bag.contains(OneBased(0)));
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+
+        assert_eq!(bag.try_insert(OneBased(0)), None);
+        assert_eq!(PrimeBag16::<OneBased>::try_from_iter([OneBased(0)]), None);
+    }
+
+    #[test]
+    fn test_nibble_round_trip() {
+        let elements = [0, 0, 1, 7, 15].map(Nibble);
+        let bag = PrimeBag32::<Nibble>::try_from_iter(elements).unwrap();
+
+        assert_eq!(bag.count_instances(Nibble(0)), 2);
+        assert!(!bag.contains(Nibble(16)));
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+
+        for value in 0..16 {
+            let nibble = Nibble::new(value).unwrap();
+            assert_eq!(Nibble::from_prime_index(nibble.to_prime_index()), nibble);
+        }
+        assert_eq!(Nibble::new(16), None);
+        assert_eq!(bag.try_insert(Nibble(16)), None);
+        assert_eq!(bag.try_insert(Nibble(u8::MAX)), None);
+    }
+
+    #[test]
+    fn test_cantor_pair() {
+        for value in 0..1000 {
+            let (a, b) = cantor_unpair(value);
+            assert_eq!(cantor_pair(a, b), value);
+        }
+        for a in 0..20 {
+            for b in 0..20 {
+                assert_eq!(cantor_unpair(cantor_pair(a, b)), (a, b));
+            }
+        }
+        let (a, b) = cantor_unpair(usize::MAX);
+        assert_eq!(cantor_pair(a, b), usize::MAX);
+    }
+}
@@ -0,0 +1,47 @@
+use core::fmt::Display;
+
+/// An error produced by a bag operation
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrimeBagError {
+    /// The resulting bag would be too large
+    CapacityExceeded,
+    /// The prime index of an element is not supported
+    IndexOutOfRange,
+    /// The bag was required to be a subset of another bag but was not
+    NotASubset,
+}
+
+impl Display for PrimeBagError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PrimeBagError::CapacityExceeded => write!(f, "The resulting bag would be too large"),
+            PrimeBagError::IndexOutOfRange => write!(f, "The prime index is out of range"),
+            PrimeBagError::NotASubset => write!(f, "The bag is not a subset"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrimeBagError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            PrimeBagError::CapacityExceeded.to_string(),
+            "The resulting bag would be too large"
+        );
+        assert_eq!(
+            PrimeBagError::IndexOutOfRange.to_string(),
+            "The prime index is out of range"
+        );
+        assert_eq!(
+            PrimeBagError::NotASubset.to_string(),
+            "The bag is not a subset"
+        );
+    }
+}
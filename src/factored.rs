@@ -0,0 +1,210 @@
+use core::fmt::Debug;
+use core::num::{NonZeroU8, NonZeroUsize};
+
+use crate::helpers::{Helpers128, Helpers16, Helpers32, Helpers64, Helpers8};
+use crate::{PrimeBag128, PrimeBag16, PrimeBag32, PrimeBag64, PrimeBag8, PrimeBagElement};
+
+macro_rules! factored_bag {
+    ($factored_x: ident, $bag_x: ident, $helpers_x: ty) => {
+        /// A bag along with its precomputed groups of elements and their counts.
+        /// Queries do not need to factorize the bag again, so this is faster when making many queries on the same bag.
+        /// Create this with `factored` and convert back with `to_bag`
+        pub struct $factored_x<E> {
+            bag: $bag_x<E>,
+            groups: [(u8, NonZeroU8); <$helpers_x>::MAX_DISTINCT],
+            len: usize,
+        }
+
+        impl<E> Clone for $factored_x<E> {
+            #[inline]
+            fn clone(&self) -> Self {
+                Self {
+                    bag: self.bag,
+                    groups: self.groups,
+                    len: self.len,
+                }
+            }
+        }
+
+        impl<E> Debug for $factored_x<E> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($factored_x))
+                    .field("bag", &self.bag)
+                    .field("groups", &&self.groups[..self.len])
+                    .finish()
+            }
+        }
+
+        impl<E> $bag_x<E> {
+            /// Factorize this bag so that repeated queries are cheap.
+            #[must_use]
+            pub const fn factored(&self) -> $factored_x<E> {
+                let mut groups = [(0u8, NonZeroU8::MIN); <$helpers_x>::MAX_DISTINCT];
+                let mut len = 0usize;
+                let mut chunk = self.0;
+                let mut prime_index = 0usize;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index)
+                {
+                    // prime indices are less than 256 and counts are less than 128 so this cannot truncate
+                    #[allow(clippy::cast_possible_truncation)]
+                    if let Some(count) = NonZeroU8::new(count as u8) {
+                        groups[len] = (index as u8, count);
+                        len += 1;
+                    }
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                $factored_x {
+                    bag: *self,
+                    groups,
+                    len,
+                }
+            }
+        }
+
+        impl<E> $factored_x<E> {
+            /// Convert back to the bag
+            #[inline]
+            #[must_use]
+            pub const fn to_bag(&self) -> $bag_x<E> {
+                self.bag
+            }
+
+            /// Returns the number of distinct elements in the bag
+            #[inline]
+            #[must_use]
+            pub const fn count_distinct(&self) -> usize {
+                self.len
+            }
+
+            /// Returns the number of elements in the bag
+            #[inline]
+            #[must_use]
+            pub fn count(&self) -> usize {
+                self.groups[..self.len]
+                    .iter()
+                    .map(|(_, count)| count.get() as usize)
+                    .sum()
+            }
+
+            /// Returns the number of instances of the element with prime index `index` in the bag
+            #[inline]
+            #[must_use]
+            pub fn count_index(&self, index: usize) -> usize {
+                let Ok(index) = u8::try_from(index) else {
+                    return 0;
+                };
+                match self.groups[..self.len].binary_search_by_key(&index, |(i, _)| *i) {
+                    Ok(position) => self.groups[position].1.get() as usize,
+                    Err(_) => 0,
+                }
+            }
+        }
+
+        impl<E: PrimeBagElement> $factored_x<E> {
+            /// Returns the number of instances of `value` in the bag
+            #[inline]
+            #[must_use]
+            pub fn count_instances(&self, value: E) -> usize {
+                self.count_index(value.to_prime_index())
+            }
+
+            /// Returns whether the bag contains a particular `value`.
+            #[inline]
+            #[must_use]
+            pub fn contains(&self, value: E) -> bool {
+                self.count_instances(value) > 0
+            }
+
+            /// Iterate through groups of elements, each item of the iterator will be the element and its count.
+            /// Groups are returned in the same order as `iter_groups` on the bag
+            #[inline]
+            pub fn iter_groups(&self) -> impl ExactSizeIterator<Item = (E, NonZeroUsize)> + '_ {
+                self.groups[..self.len].iter().map(|(index, count)| {
+                    (
+                        E::from_prime_index(*index as usize),
+                        NonZeroUsize::from(*count),
+                    )
+                })
+            }
+        }
+    };
+}
+
+factored_bag!(FactoredBag8, PrimeBag8, Helpers8);
+factored_bag!(FactoredBag16, PrimeBag16, Helpers16);
+factored_bag!(FactoredBag32, PrimeBag32, Helpers32);
+factored_bag!(FactoredBag64, PrimeBag64, Helpers64);
+factored_bag!(FactoredBag128, PrimeBag128, Helpers128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{for_each_width, random_full_bags};
+
+    #[test]
+    fn test_factored() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 2, 5]).unwrap();
+        let factored = bag.factored();
+
+        assert_eq!(factored.to_bag(), bag);
+        assert_eq!(factored.count(), 4);
+        assert_eq!(factored.count_distinct(), 3);
+        assert_eq!(factored.count_instances(0), 2);
+        assert_eq!(factored.count_instances(1), 0);
+        assert!(factored.contains(5));
+        assert!(!factored.contains(1000));
+
+        let groups: Vec<_> = factored.iter_groups().collect();
+        let expected: Vec<_> = bag.iter_groups().collect();
+        assert_eq!(groups, expected);
+
+        let empty = PrimeBag16::<usize>::EMPTY.factored();
+        assert_eq!(empty.count(), 0);
+        assert_eq!(empty.iter_groups().next(), None);
+    }
+
+    #[test]
+    fn test_factored_clone_and_debug() {
+        struct NotDebug;
+
+        impl PrimeBagElement for NotDebug {
+            fn to_prime_index(&self) -> usize {
+                0
+            }
+
+            fn from_prime_index(_: usize) -> Self {
+                Self
+            }
+        }
+
+        let bag = PrimeBag16::<NotDebug>::try_from_iter([NotDebug, NotDebug]).unwrap();
+        let factored = bag.factored();
+        assert_eq!(factored.clone().to_bag(), bag);
+        assert_eq!(
+            format!("{factored:?}"),
+            "FactoredBag16 { bag: PrimeBag16(4), groups: [(0, 2)] }"
+        );
+    }
+
+    #[test]
+    fn test_factored_random() {
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let factored = bag.factored();
+
+                assert_eq!(factored.to_bag(), bag);
+                assert_eq!(factored.count(), bag.count());
+                assert_eq!(factored.count_distinct(), bag.count_distinct());
+                for index in 0..40 {
+                    assert_eq!(factored.count_instances(index), bag.count_instances(index));
+                    assert_eq!(factored.contains(index), bag.contains(index));
+                }
+                assert!(factored.iter_groups().eq(bag.iter_groups()));
+                assert_eq!(factored.iter_groups().len(), bag.iter_groups().len());
+            }
+        });
+    }
+}
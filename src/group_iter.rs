@@ -1,15 +1,16 @@
 use core::{marker::PhantomData, num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize}};
 
 use crate::helpers::{Helpers128, Helpers16, Helpers32, Helpers64, Helpers8};
-use crate::PrimeBagElement;
+use crate::{PrimeBag128, PrimeBag16, PrimeBag32, PrimeBag64, PrimeBag8, PrimeBagElement};
 
 macro_rules! prime_bag_group_iter {
-    ($iter_x: ident, $helpers_x: ty, $nonzero_ux: ty) => {
+    ($iter_x: ident, $rest_iter_x: ident, $bag_x: ident, $helpers_x: ty, $nonzero_ux: ty) => {
         /// Iterates through groups of elements in the bag
         #[derive(Debug, Clone)]
         pub struct $iter_x<E: PrimeBagElement> {
             chunk: $nonzero_ux,
             prime_index: usize,
+            remaining_distinct: usize,
             phantom: PhantomData<E>,
         }
 
@@ -17,18 +18,49 @@ impl<E: PrimeBagElement> Iterator for $iter_x<E> {
     type Item = (E, core::num::NonZeroUsize);
 
     fn next(&mut self) -> Option<Self::Item> {
+        let (prime_index, count, rest) = <$helpers_x>::next_group(self.chunk, self.prime_index)?;
+        self.chunk = rest;
+        self.prime_index = prime_index + 1;
+        self.remaining_distinct = self.remaining_distinct.saturating_sub(1);
+        let count = NonZeroUsize::new(count as usize).unwrap_or(NonZeroUsize::MIN);
+        Some((E::from_prime_index(prime_index), count))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining_distinct, Some(self.remaining_distinct))
+    }
+}
+
+impl<E: PrimeBagElement> ExactSizeIterator for $iter_x<E> {}
+
+impl<E: PrimeBagElement> DoubleEndedIterator for $iter_x<E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
         if self.chunk == <$helpers_x>::ONE {
             return None;
         }
 
+        let twos = if self.prime_index == 0 { self.chunk.trailing_zeros() } else { 0 };
+        let rest = <$nonzero_ux>::new(self.chunk.get() >> twos).unwrap_or(<$helpers_x>::ONE);
+
+        if rest == <$helpers_x>::ONE {
+            self.chunk = <$helpers_x>::ONE;
+            self.remaining_distinct = self.remaining_distinct.saturating_sub(1);
+            let count = NonZeroUsize::new(twos as usize).unwrap_or(NonZeroUsize::MIN);
+            return Some((E::from_prime_index(0), count));
+        }
+
+        let mut prime_index = match <$helpers_x>::find_largest_possible_prime(self.prime_index.max(1), rest) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        };
+
         loop {
-            //if prime_index == 0, use trailing_zeros
-            let prime = <$helpers_x>::get_prime(self.prime_index)?;
+            prime_index = prime_index.checked_sub(1)?;
+            let prime = <$helpers_x>::get_prime(prime_index)?;
 
             if let Some(new_chunk) = <$helpers_x>::div_exact(self.chunk, prime) {
                 self.chunk = new_chunk;
-                let e = E::from_prime_index(self.prime_index);
-                self.prime_index += 1;
                 let mut count: NonZeroUsize = NonZeroUsize::MIN;
 
                 while let Some(new_chunk) = <$helpers_x>::div_exact(self.chunk, prime) {
@@ -36,9 +68,9 @@ impl<E: PrimeBagElement> Iterator for $iter_x<E> {
                     count = count.saturating_add(1);
                 }
 
-                return Some((e, count));
+                self.remaining_distinct = self.remaining_distinct.saturating_sub(1);
+                return Some((E::from_prime_index(prime_index), count));
             }
-            self.prime_index += 1;
         }
     }
 }
@@ -48,17 +80,44 @@ impl<E: PrimeBagElement> $iter_x<E> {
         Self {
             chunk,
             prime_index: 0,
+            remaining_distinct: <$helpers_x>::count_distinct_chunk(chunk),
             phantom: PhantomData,
         }
     }
 }
+
+        /// Iterates through groups of elements in the bag along with the rest of the bag after removing that group and all previous groups
+        #[derive(Debug, Clone)]
+        pub struct $rest_iter_x<E: PrimeBagElement>($iter_x<E>);
+
+        impl<E: PrimeBagElement> Iterator for $rest_iter_x<E> {
+            type Item = ((E, NonZeroUsize), $bag_x<E>);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let group = self.0.next()?;
+                Some((group, $bag_x::from_inner(self.0.chunk)))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<E: PrimeBagElement> ExactSizeIterator for $rest_iter_x<E> {}
+
+        impl<E: PrimeBagElement> $rest_iter_x<E> {
+            pub(crate) const fn new(chunk: $nonzero_ux) -> Self {
+                Self($iter_x::new(chunk))
+            }
+        }
     }
 }
 
-prime_bag_group_iter!(PrimeBagGroupIter8, Helpers8, NonZeroU8);
-prime_bag_group_iter!(PrimeBagGroupIter16, Helpers16, NonZeroU16);
-prime_bag_group_iter!(PrimeBagGroupIter32, Helpers32, NonZeroU32);
-prime_bag_group_iter!(PrimeBagGroupIter64, Helpers64, NonZeroU64);
-prime_bag_group_iter!(PrimeBagGroupIter128, Helpers128, NonZeroU128);
+prime_bag_group_iter!(PrimeBagGroupIter8, PrimeBagGroupRestIter8, PrimeBag8, Helpers8, NonZeroU8);
+prime_bag_group_iter!(PrimeBagGroupIter16, PrimeBagGroupRestIter16, PrimeBag16, Helpers16, NonZeroU16);
+prime_bag_group_iter!(PrimeBagGroupIter32, PrimeBagGroupRestIter32, PrimeBag32, Helpers32, NonZeroU32);
+prime_bag_group_iter!(PrimeBagGroupIter64, PrimeBagGroupRestIter64, PrimeBag64, Helpers64, NonZeroU64);
+prime_bag_group_iter!(PrimeBagGroupIter128, PrimeBagGroupRestIter128, PrimeBag128, Helpers128, NonZeroU128);
 
 
@@ -42,6 +42,31 @@ macro_rules! helpers {
 
             pub const NUM_PRIMES: usize = $num_primes;
 
+            /// The maximum number of copies of the element with each prime index which will fit in an otherwise empty bag
+            pub(crate) const MAX_TOTAL_FOR_SINGLE_ELEMENT: [u32; Self::NUM_PRIMES] = {
+                let mut arr = [0u32; Self::NUM_PRIMES];
+                let mut index: usize = 0;
+                while index < arr.len() {
+                    arr[index] = <$ux>::MAX.ilog(Self::PRIMES[index].get());
+                    index += 1;
+                }
+                arr
+            };
+
+            /// The maximum number of distinct elements which can fit in a bag
+            pub(crate) const MAX_DISTINCT: usize = {
+                let mut product = Self::ONE;
+                let mut index: usize = 0;
+                while index < Self::NUM_PRIMES {
+                    match product.checked_mul(Self::PRIMES[index]) {
+                        Some(p) => product = p,
+                        None => break,
+                    }
+                    index += 1;
+                }
+                index
+            };
+
             #[inline]
             pub const fn get_prime(i: usize) -> Option<$nonzero_ux> {
                 if i < Self::PRIMES.len() {
@@ -140,6 +165,76 @@ macro_rules! helpers {
                 }
             }
 
+            /// Find the first prime with index `prime_index` or greater which divides the chunk and divide out every copy of it.
+            /// Returns the index of that prime, the number of copies, and the rest of the chunk.
+            /// Returns `None` if the chunk is `ONE` or if no remaining prime in the table divides it.
+            /// To factorize a chunk, call this repeatedly with the rest of the chunk and the next prime index.
+            #[inline]
+            pub(crate) const fn next_group(
+                chunk: $nonzero_ux,
+                mut prime_index: usize,
+            ) -> Option<(usize, u32, $nonzero_ux)> {
+                if chunk.get() == 1 {
+                    return None;
+                }
+
+                if prime_index == 0 {
+                    let tz = chunk.trailing_zeros();
+                    if tz > 0 {
+                        let Some(rest) = <$nonzero_ux>::new(chunk.get() >> tz) else {
+                            unreachable!() // shifting out the trailing zeros leaves the lowest set bit
+                        };
+                        return Some((0, tz, rest));
+                    }
+                    prime_index = 1;
+                }
+
+                while prime_index < Self::NUM_PRIMES {
+                    let prime = Self::PRIMES[prime_index];
+                    if let Some(mut rest) = Self::div_exact(chunk, prime) {
+                        let mut count = 1u32;
+                        while let Some(new_rest) = Self::div_exact(rest, prime) {
+                            rest = new_rest;
+                            count += 1;
+                        }
+                        return Some((prime_index, count, rest));
+                    }
+                    prime_index += 1;
+                }
+                None
+            }
+
+            /// Count the number of distinct primes which divide the chunk
+            #[inline]
+            pub(crate) const fn count_distinct_chunk(mut chunk: $nonzero_ux) -> usize {
+                let mut count = 0usize;
+                let mut prime_index = 0usize;
+
+                while let Some((index, _, rest)) = Self::next_group(chunk, prime_index) {
+                    count += 1;
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                count
+            }
+
+            /// Returns a mask with bit `i` set if the chunk is a multiple of the prime with index `i`
+            /// Primes with index 128 or greater are not represented in the mask
+            #[inline]
+            pub(crate) const fn present_mask_chunk(mut chunk: $nonzero_ux) -> u128 {
+                let mut mask = 0u128;
+                let mut prime_index = 0usize;
+
+                while let Some((index, _, rest)) = Self::next_group(chunk, prime_index) {
+                    if index < 128 {
+                        mask |= 1 << index;
+                    }
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                mask
+            }
+
             /// Search for the largest prime greater than or equal to number, skipping the first `skip` primes
             /// Returns `Ok(index)` if the number is prime, where `index` is the index of that prime
             /// Returns `Err(index)` if the number is not prime, where `index` is the index of the next prime after `number`
@@ -157,6 +252,22 @@ macro_rules! helpers {
     };
 }
 
+/// A stable (platform and version independent) hash of a `u128`
+/// Uses the splitmix64 finalizer on each half
+#[inline]
+pub(crate) const fn stable_hash_u128(value: u128) -> u64 {
+    const fn mix(mut z: u64) -> u64 {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let low = value as u64;
+    let high = (value >> 64) as u64;
+
+    mix(low ^ mix(high))
+}
+
 // todo I believe the euclid algorithm is faster than the binary for u8/u16/u32 but slower otherwise
 
 #[cfg(not(feature = "primes256"))]
@@ -187,9 +298,20 @@ const_assert_eq!(Helpers8::PRIMES[31].get(), 131u8);
 #[cfg(feature = "primes256")]
 const_assert_eq!(Helpers8::PRIMES[53].get(), 251u8);
 
+const_assert_eq!(Helpers8::MAX_TOTAL_FOR_SINGLE_ELEMENT[0], 7);
+const_assert_eq!(Helpers8::MAX_TOTAL_FOR_SINGLE_ELEMENT[1], 5);
+const_assert_eq!(Helpers8::MAX_TOTAL_FOR_SINGLE_ELEMENT[31], 1);
+const_assert_eq!(Helpers8::MAX_DISTINCT, 4);
+const_assert_eq!(Helpers16::MAX_DISTINCT, 6);
+const_assert_eq!(Helpers32::MAX_DISTINCT, 9);
+const_assert_eq!(Helpers64::MAX_DISTINCT, 15);
+const_assert_eq!(Helpers128::MAX_DISTINCT, 26);
+
 const_assert_eq!(Helpers16::PRIMES[0].get(), 2u16);
 const_assert_eq!(Helpers16::PRIMES[1].get(), 3u16);
 const_assert_eq!(Helpers16::PRIMES[31].get(), 131u16);
+const_assert_eq!(Helpers16::MAX_TOTAL_FOR_SINGLE_ELEMENT[0], 15);
+const_assert_eq!(Helpers16::MAX_TOTAL_FOR_SINGLE_ELEMENT[31], 2);
 #[cfg(feature = "primes256")]
 const_assert_eq!(Helpers16::PRIMES[255].get(), 1619u16);
 
@@ -202,6 +324,7 @@ const_assert_eq!(Helpers64::PRIMES[31].get(), 131u64);
 const_assert_eq!(Helpers64::PRIMES[255].get(), 1619u64);
 
 const_assert_eq!(Helpers128::PRIMES[31].get(), 131u128);
+const_assert_eq!(Helpers128::MAX_TOTAL_FOR_SINGLE_ELEMENT[0], 127);
 #[cfg(feature = "primes256")]
 const_assert_eq!(Helpers128::PRIMES[255].get(), 1619u128);
 
@@ -5,8 +5,9 @@ use crate::helpers::{Helpers128, Helpers16, Helpers32, Helpers64, Helpers8};
 use crate::PrimeBagElement;
 
 macro_rules! prime_bag_iter {
-    ($iter_x: ident, $helpers_x: ty, $nonzero_ux: ty) => {
+    ($iter_x: ident, $clone_iter_x: ident, $helpers_x: ty, $nonzero_ux: ty) => {
         /// Iterate through elements of a prime bag
+        /// Elements are returned in ascending order of prime index
         #[derive(Debug, Clone)]
         pub struct $iter_x<E: PrimeBagElement> {
             chunk: $nonzero_ux,
@@ -72,6 +73,7 @@ macro_rules! prime_bag_iter {
             }
 
             //Don't implement min and max as we do not know the ordering of the prime bag elements
+            //try_fold cannot be implemented on stable but `next` divides lazily so the default implementation stops as soon as the closure breaks
 
             #[inline]
             fn last(mut self) -> Option<Self::Item>
@@ -88,6 +90,7 @@ macro_rules! prime_bag_iter {
                 <$helpers_x>::count_chunk(self.chunk, self.prime_index)
             }
 
+            #[allow(clippy::cast_possible_truncation)]
             fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
                 if self.prime_index == 0 {
                     let tz = self.chunk.trailing_zeros();
@@ -120,7 +123,7 @@ macro_rules! prime_bag_iter {
         impl<E: PrimeBagElement> core::iter::FusedIterator for $iter_x<E> {}
 
         impl<E: PrimeBagElement> DoubleEndedIterator for $iter_x<E> {
-            //todo rfold, nth_back
+            //todo rfold
 
             /// Note the performance of this is not great if called repeatedly - we have to do a bitshift and a binary search every time
             fn next_back(&mut self) -> Option<Self::Item> {
@@ -165,12 +168,131 @@ macro_rules! prime_bag_iter {
                     }
                 }
             }
+
+            /// Only does one binary search and skips whole groups of elements, rather than calling `next_back` `n` times
+            fn nth_back(&mut self, mut n: usize) -> Option<Self::Item> {
+                let twos = if self.prime_index == 0 {
+                    self.chunk.trailing_zeros()
+                } else {
+                    0
+                };
+                let start_index = self.prime_index.max(1);
+                // The remaining elements other than those with prime index 0
+                let mut rest =
+                    <$nonzero_ux>::try_from(self.chunk.get() >> twos).unwrap_or(<$nonzero_ux>::MIN);
+
+                let mut prime_index =
+                    match <$helpers_x>::find_largest_possible_prime(start_index, rest) {
+                        Ok(index) => index + 1,
+                        Err(index) => index,
+                    };
+
+                while rest != <$nonzero_ux>::MIN {
+                    prime_index = prime_index.checked_sub(1)?;
+                    let prime = <$helpers_x>::get_prime(prime_index)?;
+
+                    while let Some(new_rest) = <$helpers_x>::div_exact(rest, prime) {
+                        rest = new_rest;
+                        if n == 0 {
+                            self.chunk = <$nonzero_ux>::try_from(rest.get() << twos)
+                                .unwrap_or(<$nonzero_ux>::MIN);
+                            return Some(Self::Item::from_prime_index(prime_index));
+                        }
+                        n -= 1;
+                    }
+                }
+
+                if n < twos as usize {
+                    // n < twos so this cannot truncate
+                    #[allow(clippy::cast_possible_truncation)]
+                    let shift = n as u32 + 1;
+                    self.chunk =
+                        <$nonzero_ux>::try_from(<$nonzero_ux>::MIN.get() << (twos - shift))
+                            .unwrap_or(<$nonzero_ux>::MIN);
+                    Some(Self::Item::from_prime_index(0))
+                } else {
+                    self.chunk = <$nonzero_ux>::MIN;
+                    None
+                }
+            }
+        }
+
+        /// Iterate through elements of a prime bag, creating each distinct element once and cloning it for repeats
+        #[derive(Debug, Clone)]
+        pub struct $clone_iter_x<E: PrimeBagElement + Clone> {
+            chunk: $nonzero_ux,
+            prime_index: usize,
+            current: Option<(E, usize)>,
+        }
+
+        impl<E: PrimeBagElement + Clone> $clone_iter_x<E> {
+            pub(crate) const fn new(chunk: $nonzero_ux) -> Self {
+                Self {
+                    chunk,
+                    prime_index: 0,
+                    current: None,
+                }
+            }
         }
+
+        impl<E: PrimeBagElement + Clone> Iterator for $clone_iter_x<E> {
+            type Item = E;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                if let Some((element, remaining)) = &mut self.current {
+                    if *remaining > 1 {
+                        *remaining -= 1;
+                        return Some(element.clone());
+                    }
+                    return self.current.take().map(|(element, _)| element);
+                }
+
+                if self.chunk == <$helpers_x>::ONE {
+                    return None;
+                }
+
+                loop {
+                    let prime = <$helpers_x>::get_prime(self.prime_index)?;
+                    let mut count = 0usize;
+                    while let Some(new_chunk) = <$helpers_x>::div_exact(self.chunk, prime) {
+                        self.chunk = new_chunk;
+                        count += 1;
+                    }
+                    let prime_index = self.prime_index;
+                    self.prime_index += 1;
+
+                    if count > 0 {
+                        let element = E::from_prime_index(prime_index);
+                        if count > 1 {
+                            self.current = Some((element.clone(), count - 1));
+                        }
+                        return Some(element);
+                    }
+                }
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let remaining = self.current.as_ref().map_or(0, |(_, remaining)| *remaining);
+                let rest = <$helpers_x>::count_chunk(self.chunk, 0);
+                (remaining + rest, Some(remaining + rest))
+            }
+        }
+
+        impl<E: PrimeBagElement + Clone> ExactSizeIterator for $clone_iter_x<E> {}
+
+        impl<E: PrimeBagElement + Clone> core::iter::FusedIterator for $clone_iter_x<E> {}
     };
 }
 
-prime_bag_iter!(PrimeBagIter8, Helpers8, NonZeroU8);
-prime_bag_iter!(PrimeBagIter16, Helpers16, NonZeroU16);
-prime_bag_iter!(PrimeBagIter32, Helpers32, NonZeroU32);
-prime_bag_iter!(PrimeBagIter64, Helpers64, NonZeroU64);
-prime_bag_iter!(PrimeBagIter128, Helpers128, NonZeroU128);
+prime_bag_iter!(PrimeBagIter8, PrimeBagCloneIter8, Helpers8, NonZeroU8);
+prime_bag_iter!(PrimeBagIter16, PrimeBagCloneIter16, Helpers16, NonZeroU16);
+prime_bag_iter!(PrimeBagIter32, PrimeBagCloneIter32, Helpers32, NonZeroU32);
+prime_bag_iter!(PrimeBagIter64, PrimeBagCloneIter64, Helpers64, NonZeroU64);
+prime_bag_iter!(
+    PrimeBagIter128,
+    PrimeBagCloneIter128,
+    Helpers128,
+    NonZeroU128
+);
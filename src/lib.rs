@@ -27,7 +27,15 @@
 //! |    Intersection     | Greatest Common Factor |
 //!
 //! Elements of the Bag must implement `PrimeBagElement`
+//! Implementations for `core::cmp::Ordering`, `bool`, `Option<T>` and tuples, and the `OneBased` and `Nibble` elements, are provided by the `common-elements` feature
+//! For fieldless enums, `PrimeBagElement` can be implemented using the `impl_prime_bag_element!` macro
+//! For quick experiments, `IndexElement` is an element which is just its prime index
+//!
+//! This crate is `no_std`. The `std` feature implements `std::error::Error` for `PrimeBagError` and enables conversion to and from `HashMap`
+//! The `alloc` feature enables methods which return a `Vec`
+//! The `total-content-ord` feature orders bags by their number of elements and then by their sorted elements instead of by their inner values. Note that this changes the iteration order of `BTreeSet` and `BTreeMap` keyed by bags
 //! Currently only 128 different element values are supported, but if necessary I could increase this
+//! Bags are backed by `NonZeroU8` to `NonZeroU128`. The prime arithmetic on these is internal, so other backing integers are not supported
 //!
 //!
 //! ## Getting started
@@ -60,26 +68,59 @@
 #[macro_use]
 extern crate static_assertions;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[macro_use]
+mod macros;
+
+/// Bags with a backing integer chosen at runtime
+pub mod any;
+/// Builders which insert elements through a mutable reference
+pub mod builder;
+mod element;
+/// Errors produced by bag operations
+pub mod error;
+/// Bags with precomputed factorizations
+pub mod factored;
 /// Iterator of groups of elements
 pub mod group_iter;
 mod helpers;
 /// Iterator of elements
 pub mod iter;
+/// Helpers for testing element implementations
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
+use any::AnyPrimeBag;
 use core::fmt::Debug;
 use core::hash::Hash;
 use core::marker::PhantomData;
 use core::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize};
+#[cfg(feature = "common-elements")]
+pub use element::common::{Nibble, OneBased};
+pub use element::IndexElement;
+use error::PrimeBagError;
 use group_iter::{
     PrimeBagGroupIter128, PrimeBagGroupIter16, PrimeBagGroupIter32, PrimeBagGroupIter64,
-    PrimeBagGroupIter8,
+    PrimeBagGroupIter8, PrimeBagGroupRestIter128, PrimeBagGroupRestIter16, PrimeBagGroupRestIter32,
+    PrimeBagGroupRestIter64, PrimeBagGroupRestIter8,
 };
 
 use crate::{
-    helpers::{Helpers128, Helpers16, Helpers32, Helpers64, Helpers8},
-    iter::{PrimeBagIter128, PrimeBagIter16, PrimeBagIter32, PrimeBagIter64, PrimeBagIter8},
+    helpers::{stable_hash_u128, Helpers128, Helpers16, Helpers32, Helpers64, Helpers8},
+    iter::{
+        PrimeBagCloneIter128, PrimeBagCloneIter16, PrimeBagCloneIter32, PrimeBagCloneIter64,
+        PrimeBagCloneIter8, PrimeBagIter128, PrimeBagIter16, PrimeBagIter32, PrimeBagIter64,
+        PrimeBagIter8,
+    },
 };
 
+/// The number of prime indices which elements can use.
+/// This is `32`, or `256` if the `primes256` feature is specified.
+/// Note that smaller bags may not have room for elements with the largest indices.
+pub const NUM_PRIME_INDICES: usize = Helpers128::NUM_PRIMES;
+
 /// Indicates a type that can be put into a Prime Bag
 /// To implement correctly, every possible value of this type must map to a unique number
 /// And that number must map back to that element.
@@ -98,11 +139,47 @@ pub trait PrimeBagElement {
     fn from_prime_index(value: usize) -> Self;
 }
 
+/// Returns the prime index of `prime` if it is one of the primes used by the bags.
+/// Returns `None` if `prime` is not prime or is too large to have a prime index.
+/// This can be used to validate inner values which were not created by this crate.
+#[must_use]
+pub fn index_of_prime_u64(prime: u64) -> Option<usize> {
+    let prime = NonZeroU128::new(u128::from(prime))?;
+    Helpers128::find_largest_possible_prime(0, prime).ok()
+}
+
+/// Formats using the wrapped function
+struct DebugWith<F: Fn(&mut core::fmt::Formatter<'_>) -> core::fmt::Result>(F);
+
+impl<F: Fn(&mut core::fmt::Formatter<'_>) -> core::fmt::Result> Debug for DebugWith<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// Checks that the element created from `index` maps back to `index`.
+/// If this fails then two different indices map to the same element, so bags containing them would be merged.
+/// The check only runs when debug assertions are enabled, so this is intended for use in tests.
+///
+/// # Panics
+///
+/// Panics if debug assertions are enabled and `E::from_prime_index(index).to_prime_index() != index`
+#[inline]
+pub fn debug_check_element_roundtrip<E: PrimeBagElement>(index: usize) {
+    debug_assert_eq!(
+        E::from_prime_index(index).to_prime_index(),
+        index,
+        "element created from prime index {index} does not map back to it"
+    );
+}
+
 macro_rules! prime_bag {
     ($bag_x: ident, $helpers_x: ty, $nonzero_ux: ty, $ux: ty) => {
         /// Represents a bag (multi-set) of elements
         /// The bag will have a maximum capacity
         /// Use larger sized bags (e.g. `PrimeBag64`, `PrimeBag128`) to store more elements
+        /// The bag is guaranteed to have the same layout as its inner value
+        #[repr(transparent)]
         pub struct $bag_x<E>($nonzero_ux, PhantomData<E>);
 
         assert_eq_size!($bag_x<usize>, $ux);
@@ -123,12 +200,35 @@ macro_rules! prime_bag {
 
         impl<E> Eq for $bag_x<E> {}
 
+        /// Bags are ordered by their inner values.
+        /// This is fast but does not reflect the contents of the bags in any meaningful way.
+        #[cfg(not(feature = "total-content-ord"))]
         impl<E> Ord for $bag_x<E> {
             fn cmp(&self, other: &Self) -> core::cmp::Ordering {
                 self.0.cmp(&other.0)
             }
         }
 
+        /// Bags are ordered by their number of elements and then lexicographically by the prime indices of their elements in ascending order.
+        /// So a bag is always less than its strict supersets.
+        /// Inner values with a prime factor which does not correspond to a supported prime index (such as `MAX`) are greater than every valid bag and are ordered by their inner values.
+        #[cfg(feature = "total-content-ord")]
+        impl<E> Ord for $bag_x<E> {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                let self_valid = Self::from_inner_checked(self.0).is_ok();
+                let other_valid = Self::from_inner_checked(other.0).is_ok();
+
+                match (self_valid, other_valid) {
+                    (true, true) => self
+                        .count()
+                        .cmp(&other.count())
+                        .then_with(|| self.into_index_iter().cmp(other.into_index_iter())),
+                    (false, false) => self.0.cmp(&other.0),
+                    _ => other_valid.cmp(&self_valid),
+                }
+            }
+        }
+
         impl<E> PartialOrd for $bag_x<E> {
             fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
                 Some(self.cmp(other))
@@ -143,7 +243,42 @@ macro_rules! prime_bag {
 
         impl<E> Debug for $bag_x<E> {
             fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                f.debug_tuple("PrimeBag128").field(&self.0).finish()
+                f.debug_tuple(stringify!($bag_x)).field(&self.0).finish()
+            }
+        }
+
+        /// Multiplying bags gives their sum (as a multiset), because the inner values are multiplied.
+        /// This is the same as folding with `try_sum`.
+        /// The product of an empty iterator is the empty bag.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the resulting bag would be too large.
+        impl<E> core::iter::Product for $bag_x<E> {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::EMPTY, |acc, bag| {
+                    acc.try_sum(&bag).expect("The resulting bag would be too large")
+                })
+            }
+        }
+
+        /// Multiplying bags gives their sum (as a multiset), because the inner values are multiplied.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the resulting bag would be too large.
+        impl<'a, E> core::iter::Product<&'a Self> for $bag_x<E> {
+            fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().product()
+            }
+        }
+
+        impl<E> TryFrom<$nonzero_ux> for $bag_x<E> {
+            type Error = PrimeBagError;
+
+            #[inline]
+            fn try_from(value: $nonzero_ux) -> Result<Self, Self::Error> {
+                Self::from_inner_checked(value)
             }
         }
 
@@ -173,6 +308,26 @@ macro_rules! prime_bag {
                 Some(Self(b, PhantomData))
             }
 
+            /// Try to extend the bag with elements from an iterator of elements and counts.
+            /// Does not modify this bag.
+            /// Each element is inserted a number of times equal to its count, and elements with a count of zero are ignored.
+            /// Returns `None` if the resulting bag would be too large
+            #[must_use]
+            #[inline]
+            pub fn try_extend_counts<T: IntoIterator<Item = (E, u32)>>(&self, iter: T) -> Option<Self> {
+                let mut b = self.0;
+                for (e, count) in iter {
+                    if count == 0 {
+                        continue;
+                    }
+                    let u: usize = e.to_prime_index();
+                    let p = <$helpers_x>::get_prime(u)?.checked_pow(count)?;
+                    b = b.checked_mul(p)?;
+                }
+
+                Some(Self(b, PhantomData))
+            }
+
             /// Tries to create a bag from an iterator of values.
             /// Returns `None` if the resulting bag would be too large.
             #[must_use]
@@ -181,13 +336,76 @@ macro_rules! prime_bag {
                 Self::default().try_extend(iter)
             }
 
+            /// Tries to create a bag from an iterator of values.
+            ///
+            /// # Errors
+            ///
+            /// Returns `IndexOutOfRange` if the prime index of any value is out of range.
+            /// Returns `CapacityExceeded` if the resulting bag would be too large.
+            #[inline]
+            pub fn from_iter_checked<T: IntoIterator<Item = E>>(iter: T) -> Result<Self, PrimeBagError> {
+                let mut b = Self::default();
+                for e in iter {
+                    b = b.try_insert_result(e)?;
+                }
+                Ok(b)
+            }
+
+            /// Try to create the union of this bag and the elements of an iterator.
+            /// Does not modify this bag.
+            /// Returns `None` if the resulting bag would be too large.
+            /// The union contains each element a number of times equal to the maximum of its count in this bag and its count in the iterator.
+            #[must_use]
+            #[inline]
+            pub fn try_union_with_iter<T: IntoIterator<Item = E>>(&self, iter: T) -> Option<Self> {
+                // The union is a superset of the tally so if the tally overflows, so would the union
+                let tally = Self::try_from_iter(iter)?;
+                self.try_union(&tally)
+            }
+
+            /// Try to create a bag by mapping each element of this bag to a bag and summing the results.
+            /// `f` is called once for each instance of each element.
+            /// Returns `None` if the resulting bag would be too large.
+            #[must_use]
+            #[inline]
+            pub fn flat_map_to_bag<E2, F: FnMut(E) -> $bag_x<E2>>(
+                &self,
+                mut f: F,
+            ) -> Option<$bag_x<E2>> {
+                let mut result = $bag_x::<E2>::EMPTY;
+                for e in *self {
+                    result = result.try_sum(&f(e))?;
+                }
+                Some(result)
+            }
+
+            /// Returns a value whose `Debug` implementation shows the decoded contents of the bag as well as its inner value
+            /// e.g. `PrimeBag16(45 = {1: 2, 2: 1})`
+            /// This is useful in `assert_eq!` failure messages, as the bag's own `Debug` implementation only shows the inner value.
+            #[must_use]
+            pub fn debug_contents(&self) -> impl Debug
+            where
+                E: Debug,
+            {
+                let bag = *self;
+                DebugWith(move |f| {
+                    write!(f, "{}({} = ", stringify!($bag_x), bag.0)?;
+                    f.debug_map()
+                        .entries(bag.iter_groups().map(|(e, count)| (e, count.get())))
+                        .finish()?;
+                    f.write_str(")")
+                })
+            }
+
             /// Returns the number of instances of `value` in the bag.
+            /// The count can never exceed the number of bits in the backing integer (e.g. 127 for `PrimeBag128`) so it will never truncate.
             #[must_use]
             #[inline]
             pub fn count_instances(&self, value: E) -> usize {
                 let u: usize = value.to_prime_index();
                 // todo use binary search
 
+                // The number of trailing zeros is the number of times 2 divides the inner value, even if the inner value has factors outside the prime table
                 if u == 0 {
                     return self.0.trailing_zeros() as usize;
                 }
@@ -206,6 +424,26 @@ macro_rules! prime_bag {
                 return 0;
             }
 
+            /// Returns the number of instances of `value` in the bag.
+            /// Returns `None` if the bag does not contain `value`.
+            #[must_use]
+            #[inline]
+            pub fn count_instances_nonzero(&self, value: E) -> Option<NonZeroUsize> {
+                NonZeroUsize::new(self.count_instances(value))
+            }
+
+            /// Returns the number of instances of each of the `values` in the bag.
+            /// The bag is only factorized once, so this is faster than calling `count_instances` for each value.
+            #[cfg(feature = "alloc")]
+            #[must_use]
+            pub fn count_instances_many(&self, values: &[E]) -> alloc::vec::Vec<usize> {
+                let counts = self.count_all_indices();
+                values
+                    .iter()
+                    .map(|value| counts.get(value.to_prime_index()).copied().unwrap_or_default())
+                    .collect()
+            }
+
             /// Returns whether the bag contains a particular `value`.
             #[must_use]
             #[inline]
@@ -217,17 +455,36 @@ macro_rules! prime_bag {
                 false
             }
 
+            /// Returns whether the bag contains exactly one copy of `value` and nothing else.
+            /// This is cheaper than creating a bag containing just `value` and comparing.
+            #[must_use]
+            #[inline]
+            pub fn is_exactly(&self, value: E) -> bool {
+                let u: usize = value.to_prime_index();
+                <$helpers_x>::get_prime(u) == Some(self.0)
+            }
+
             /// Returns whether the bag contains a particular `value` at least `n` times.
             #[must_use]
             #[inline]
             pub fn contains_at_least(&self, value: E, n: u32) -> bool {
                 let u: usize = value.to_prime_index();
-                if let Some(p) = <$helpers_x>::get_prime(u) {
-                    if let Some(b) = p.checked_pow(n) {
-                        return <$helpers_x>::is_multiple(self.0, b);
-                    }
+                if u == 0 {
+                    return self.0.trailing_zeros() >= n;
                 }
-                false
+                let Some(p) = <$helpers_x>::get_prime(u) else {
+                    return false;
+                };
+                // Divide rather than comparing with p^n which could overflow
+                // This stops as soon as a division fails so it takes at most `count_instances` + 1 steps
+                let mut chunk = self.0;
+                for _ in 0..n {
+                    let Some(new_chunk) = <$helpers_x>::div_exact(chunk, p) else {
+                        return false;
+                    };
+                    chunk = new_chunk;
+                }
+                true
             }
 
             /// Try to create a new bag with the `value` inserted.
@@ -242,6 +499,89 @@ macro_rules! prime_bag {
                 Some(Self(b, PhantomData))
             }
 
+            /// Try to create a new bag with the `value` inserted.
+            /// Does not modify the existing bag.
+            ///
+            /// # Errors
+            ///
+            /// Returns `IndexOutOfRange` if the prime index of `value` is out of range.
+            /// Returns `CapacityExceeded` if the bag does not have enough space.
+            #[inline]
+            pub fn try_insert_result(&self, value: E) -> Result<Self, PrimeBagError> {
+                let u: usize = value.to_prime_index();
+                let p = <$helpers_x>::get_prime(u).ok_or(PrimeBagError::IndexOutOfRange)?;
+                let b = self
+                    .0
+                    .checked_mul(p)
+                    .ok_or(PrimeBagError::CapacityExceeded)?;
+                Ok(Self(b, PhantomData))
+            }
+
+            /// Try to create a new bag with the `value` inserted.
+            /// Also returns the number of instances of `value` in this bag before the insertion.
+            /// Does not modify the existing bag.
+            /// Returns `None` if the bag does not have enough space.
+            #[must_use]
+            #[inline]
+            pub fn try_insert_returning_prev(&self, value: E) -> Option<(Self, usize)> {
+                let u: usize = value.to_prime_index();
+                let p = <$helpers_x>::get_prime(u)?;
+                let b = self.0.checked_mul(p)?;
+                let prev = self.count_instances(value);
+                Some((Self(b, PhantomData), prev))
+            }
+
+            /// Create a new bag with as many copies of `value` inserted as will fit, up to `count`.
+            /// Also returns the number of copies actually inserted.
+            /// Does not modify the existing bag.
+            /// If the prime index of `value` is out of range, no copies are inserted.
+            #[must_use]
+            #[inline]
+            pub fn insert_as_many_as_fit(&self, value: E, count: u32) -> (Self, u32) {
+                let u: usize = value.to_prime_index();
+                let Some(p) = <$helpers_x>::get_prime(u) else {
+                    return (*self, 0);
+                };
+                let mut b = self.0;
+                let mut inserted = 0u32;
+                while inserted < count {
+                    let Some(new_b) = b.checked_mul(p) else {
+                        break;
+                    };
+                    b = new_b;
+                    inserted += 1;
+                }
+                (Self(b, PhantomData), inserted)
+            }
+
+            /// Create a new bag with as many copies of `value` inserted as will fit.
+            /// Also returns the number of copies inserted.
+            /// Does not modify the existing bag.
+            /// If the prime index of `value` is out of range, no copies are inserted.
+            #[must_use]
+            #[inline]
+            pub fn fill_with(&self, value: E) -> (Self, u32) {
+                let u: usize = value.to_prime_index();
+                let Some(p) = <$helpers_x>::get_prime(u) else {
+                    return (*self, 0);
+                };
+                let room = <$ux>::MAX / self.0.get();
+                let mut power = <$helpers_x>::ONE;
+                let mut count = 0u32;
+                while let Some(next) = power.checked_mul(p) {
+                    if next.get() > room {
+                        break;
+                    }
+                    power = next;
+                    count += 1;
+                }
+
+                match self.0.checked_mul(power) {
+                    Some(b) => (Self(b, PhantomData), count),
+                    None => unreachable!(), // power is at most MAX / self
+                }
+            }
+
             /// Try to remove `value` from this bag
             /// Returns `None` if the bag does not contain `value`
             #[inline]
@@ -267,12 +607,44 @@ macro_rules! prime_bag {
                 let b = self.0.checked_mul(p2)?;
                 Some(Self(b, PhantomData))
             }
+
+            /// Try to apply a list of changes to the counts of elements.
+            /// A positive delta inserts that many copies of the element and a negative delta removes that many copies.
+            /// The changes are all-or-nothing: returns `None` if any removal would remove more copies than the bag contains or if the resulting bag would be too large.
+            /// All removals are applied before any insertions, so a removal cannot use copies inserted by the same `deltas`.
+            #[must_use]
+            #[inline]
+            pub fn try_apply_deltas(&self, deltas: &[(E, i32)]) -> Option<Self> {
+                let mut b = self.0;
+                for (e, delta) in deltas {
+                    if *delta < 0 {
+                        let p = <$helpers_x>::get_prime(e.to_prime_index())?;
+                        b = <$helpers_x>::div_exact(b, p.checked_pow(delta.unsigned_abs())?)?;
+                    }
+                }
+                for (e, delta) in deltas {
+                    if *delta > 0 {
+                        let p = <$helpers_x>::get_prime(e.to_prime_index())?;
+                        b = b.checked_mul(p.checked_pow(delta.unsigned_abs())?)?;
+                    }
+                }
+
+                Some(Self(b, PhantomData))
+            }
         }
 
         impl<E> $bag_x<E> {
             /// An empty bag
             pub const EMPTY: Self = Self(<$nonzero_ux>::MIN, PhantomData);
 
+            /// The smallest bag by `Ord`. This is the empty bag.
+            pub const MIN: Self = Self::EMPTY;
+
+            /// The bag whose inner value is the maximum value of the backing integer.
+            /// This is the largest bag by `Ord` unless the `total-content-ord` feature is enabled.
+            /// Note that this is not the bag with the most elements and may not be canonical.
+            pub const MAX: Self = Self(<$nonzero_ux>::MAX, PhantomData);
+
             /// Create a bag from the inner value
             /// This can be used to convert a bag from one type to another or to enable serialization
             #[inline]
@@ -281,21 +653,133 @@ macro_rules! prime_bag {
                 Self(inner, PhantomData)
             }
 
+            /// Create a bag from the inner value as a primitive integer
+            /// Returns `None` if `value` is zero
+            /// This is the inverse of `into_inner_primitive`
+            #[inline]
+            #[must_use]
+            pub const fn from_primitive(value: $ux) -> Option<Self> {
+                match <$nonzero_ux>::new(value) {
+                    Some(inner) => Some(Self(inner, PhantomData)),
+                    None => None,
+                }
+            }
+
+            /// Create a bag from the inner value, checking that it is valid.
+            ///
+            /// # Errors
+            ///
+            /// Returns `IndexOutOfRange` if `inner` has a prime factor which does not correspond to a supported prime index.
+            #[inline]
+            pub const fn from_inner_checked(inner: $nonzero_ux) -> Result<Self, PrimeBagError> {
+                let mut chunk = inner;
+                let mut prime_index = 0;
+
+                while let Some((index, _, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                if chunk.get() > 1 {
+                    return Err(PrimeBagError::IndexOutOfRange);
+                }
+                Ok(Self(inner, PhantomData))
+            }
+
             /// Convert the bag to the inner value
             /// This can be used to convert a bag from one type to another or to enable serialization
+            /// Every multiset has exactly one inner value, regardless of the order in which its elements were inserted,
+            /// so two bags are equal if and only if their inner values are equal.
             #[inline]
             #[must_use]
             pub const fn into_inner(self) -> $nonzero_ux {
                 self.0
             }
 
+            /// Convert the bag to the inner value as a primitive integer
+            /// This is equivalent to `self.into_inner().get()`
+            #[inline]
+            #[must_use]
+            pub const fn into_inner_primitive(self) -> $ux {
+                self.0.get()
+            }
+
+            /// Convert this bag to a `PrimeBag16`, dropping elements which do not fit.
+            /// Groups of elements are moved in ascending order of prime index until a group does not fit.
+            /// Returns the `PrimeBag16` and the remainder of this bag which did not fit.
+            #[must_use]
+            pub fn lossy_into_u16(self) -> (PrimeBag16<E>, Self) {
+                let mut fitted = Helpers16::ONE;
+                let mut chunk = self.0;
+
+                for prime in <$helpers_x>::PRIMES {
+                    if chunk == <$helpers_x>::ONE {
+                        break;
+                    }
+                    let power = Self::largest_power_dividing(chunk, prime);
+                    if power == <$helpers_x>::ONE {
+                        continue;
+                    }
+                    let Some(new_fitted) = NonZeroU16::try_from(power)
+                        .ok()
+                        .and_then(|power| fitted.checked_mul(power))
+                    else {
+                        break;
+                    };
+                    fitted = new_fitted;
+                    let Some(new_chunk) = <$helpers_x>::div_exact(chunk, power) else {
+                        unreachable!() // power divides chunk
+                    };
+                    chunk = new_chunk;
+                }
+
+                (PrimeBag16::from_inner(fitted), Self(chunk, PhantomData))
+            }
+
+            /// Convert this bag to a `PrimeBag128` containing the same elements.
+            /// This can be used to process bags of different sizes uniformly.
+            #[inline]
+            #[must_use]
+            pub fn widen(self) -> PrimeBag128<E> {
+                PrimeBag128::from(self)
+            }
+
+            /// Convert this bag to the smallest sized bag which can hold its contents.
+            #[inline]
+            #[must_use]
+            pub fn shrink(self) -> AnyPrimeBag<E> {
+                AnyPrimeBag::from(self.widen())
+            }
+
+            /// Returns a hash of the contents of this bag which does not depend on the size of the backing integer.
+            /// Bags of different sizes containing the same elements will have the same content hash.
+            /// The hash is stable across platforms and versions of this crate.
+            #[inline]
+            #[must_use]
+            pub const fn content_hash(&self) -> u64 {
+                stable_hash_u128(self.0.get() as u128)
+            }
+
             /// Returns whether this is a superset of the `rhs` bag.
             /// This is true if every element in the `rhs` bag is contained at least as many times in this.
             /// Note that this will also return true if the two bags are equal.
             #[must_use]
             #[inline]
             pub const fn is_superset(&self, rhs: &Self) -> bool {
-                <$helpers_x>::is_multiple(self.0, rhs.0)
+                // A multiple is never smaller, so this cheap check rules out many bags before the modulo
+                self.0.get() >= rhs.0.get() && <$helpers_x>::is_multiple(self.0, rhs.0)
+            }
+
+            /// Returns the number of bags in `others` which are supersets of this bag.
+            /// This is equivalent to counting the bags for which `is_superset(self)` is true, using a branch-free loop.
+            #[must_use]
+            #[inline]
+            pub fn count_supersets(&self, others: &[Self]) -> usize {
+                let divisor = self.0.get();
+                others
+                    .iter()
+                    .map(|other| usize::from(other.0.get() % divisor == 0))
+                    .sum()
             }
 
             /// Returns whether this is a subset of the `rhs` bag.
@@ -308,6 +792,7 @@ macro_rules! prime_bag {
             }
 
             /// Returns whether the bag contains zero elements.
+            /// This is always equivalent to `self.count() == 0`.
             #[must_use]
             #[inline]
             pub const fn is_empty(&self) -> bool {
@@ -326,9 +811,122 @@ macro_rules! prime_bag {
                 }
             }
 
+            /// Returns the number of bits needed to store the inner value.
+            /// If `self.approx_bits() + rhs.approx_bits()` is at most the number of bits in the backing integer then `try_sum` will succeed,
+            /// and if it is more than one greater then `try_sum` will fail.
+            /// The empty bag uses 1 bit.
+            #[must_use]
+            #[inline]
+            pub const fn approx_bits(&self) -> u32 {
+                self.0.ilog2() + 1
+            }
+
+            /// Returns whether the sum of this bag and `rhs` would fit in this size of bag.
+            /// This is true if and only if `try_sum` would return `Some`.
+            #[must_use]
+            #[inline]
+            pub const fn fits_sum(&self, rhs: &Self) -> bool {
+                self.0.checked_mul(rhs.0).is_some()
+            }
+
+            /// Try to create the sum of this bag and `rhs`.
+            ///
+            /// # Errors
+            ///
+            /// Returns `CapacityExceeded` if the resulting bag would be too large.
+            #[inline]
+            pub const fn try_sum_result(&self, rhs: &Self) -> Result<Self, PrimeBagError> {
+                match self.0.checked_mul(rhs.0) {
+                    Some(b) => Ok(Self(b, PhantomData)),
+                    None => Err(PrimeBagError::CapacityExceeded),
+                }
+            }
+
+            /// Try to create the sum of this bag and `rhs`, reporting which element caused the overflow on failure.
+            /// The elements of `rhs` are added one at a time in ascending order of prime index, so this is much slower than `try_sum`.
+            /// Use it to diagnose capacity failures.
+            ///
+            /// # Errors
+            ///
+            /// Returns the prime index of the element of `rhs` which was being added when the bag first became too large.
+            /// If `rhs` has a prime factor which does not correspond to a supported prime index (so it was not created from elements) and that factor makes the bag too large,
+            /// returns the number of prime indices this size of bag supports, which is not a valid prime index.
+            #[inline]
+            pub const fn try_sum_diagnostic(&self, rhs: &Self) -> Result<Self, usize> {
+                let mut result = self.0;
+                let mut chunk = rhs.0;
+                let mut prime_index = 0usize;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    let power = <$helpers_x>::PRIMES[index].saturating_pow(count);
+                    result = match result.checked_mul(power) {
+                        Some(r) => r,
+                        None => return Err(index),
+                    };
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                match result.checked_mul(chunk) {
+                    Some(r) => Ok(Self(r, PhantomData)),
+                    None => Err(<$helpers_x>::NUM_PRIMES),
+                }
+            }
+
+            /// Calculates the sum of this bag and `rhs`.
+            /// Returns a tuple of the sum along with a boolean indicating whether an arithmetic overflow would occur.
+            /// If an overflow would have occurred then the returned bag is meaningless and should not be used.
+            #[must_use]
+            #[inline]
+            pub const fn overflowing_sum(&self, rhs: &Self) -> (Self, bool) {
+                let (product, overflowed) = self.0.get().overflowing_mul(rhs.0.get());
+                match <$nonzero_ux>::new(product) {
+                    Some(b) => (Self(b, PhantomData), overflowed),
+                    None => (Self::EMPTY, true),
+                }
+            }
+
+            /// Try to create a bag with the count of every element multiplied by `n`.
+            /// Scaling by zero gives the empty bag.
+            /// Returns `None` if the resulting bag would be too large.
+            #[must_use]
+            #[inline]
+            pub const fn try_scale(&self, n: u32) -> Option<Self> {
+                match self.0.checked_pow(n) {
+                    Some(b) => Some(Self(b, PhantomData)),
+                    None => None,
+                }
+            }
+
+            /// Returns the largest factor that this bag can be scaled by without overflowing.
+            /// This is the number of copies of this bag which fit in this size of bag.
+            /// The empty bag can be scaled by any factor so this returns `u32::MAX`.
+            #[must_use]
+            #[inline]
+            pub const fn max_scale(&self) -> u32 {
+                if self.0.get() == 1 {
+                    return u32::MAX;
+                }
+                <$nonzero_ux>::MAX.get().ilog(self.0.get())
+            }
+
+            /// Create a bag with the count of every element multiplied by the largest factor no greater than `n` for which the result fits.
+            /// Scaling by zero gives the empty bag and scaling by one always fits, so this never fails.
+            #[must_use]
+            #[inline]
+            pub const fn saturating_scale(&self, n: u32) -> Self {
+                let max_factor = self.max_scale();
+                let factor = if n < max_factor { n } else { max_factor };
+                match self.0.checked_pow(factor) {
+                    Some(b) => Self(b, PhantomData),
+                    None => unreachable!(), // self^max_factor is at most MAX
+                }
+            }
+
             /// Try to create the union of this bag and `rhs`.
             /// Returns `None` if the resulting bag would be too large.
             /// The union contains each element that is present in either bag a number of times equal to the maximum count of that element in either bag.
+            /// This is also available as `try_elementwise_max`, which makes it clear that the counts are not added as in `try_sum`.
             #[must_use]
             #[inline]
             pub const fn try_union(&self, rhs: &Self) -> Option<Self> {
@@ -339,9 +937,61 @@ macro_rules! prime_bag {
                 Some(Self(lcm, PhantomData))
             }
 
+            /// Returns whether the union of this bag and `rhs` would fit in this size of bag.
+            /// This is true if and only if `try_union` would return `Some`.
+            #[must_use]
+            #[inline]
+            pub const fn fits_union(&self, rhs: &Self) -> bool {
+                <$helpers_x>::lcm(self.0, rhs.0).is_some()
+            }
+
+            /// Try to create the element-wise maximum of this bag and `rhs`.
+            /// This is an alias of `try_union`.
+            /// Note that this is not the same as `try_sum` which adds the counts of each element.
+            #[must_use]
+            #[inline]
+            pub const fn try_elementwise_max(&self, rhs: &Self) -> Option<Self> {
+                self.try_union(rhs)
+            }
+
+            /// Try to create the element-wise maximum of this bag and `rhs`, taking both by value.
+            /// Returns `None` if the resulting bag would be too large.
+            /// This is `try_elementwise_max` for chaining e.g. `bag.try_elementwise_max_owned(a)?.try_elementwise_max_owned(b)?`
+            #[must_use]
+            #[inline]
+            pub const fn try_elementwise_max_owned(self, rhs: Self) -> Option<Self> {
+                self.try_union(&rhs)
+            }
+
+            /// Try to create a union of this bag and `rhs` where the preferred bag decides the count of each element it contains.
+            /// If `prefer_self` is true, elements in `self` keep their count from `self` and elements only in `rhs` take their count from `rhs`.
+            /// Otherwise the roles are reversed.
+            /// Returns `None` if the resulting bag would be too large.
+            #[must_use]
+            #[inline]
+            pub fn try_weighted_union(&self, rhs: &Self, prefer_self: bool) -> Option<Self> {
+                let (preferred, other) = if prefer_self { (self, rhs) } else { (rhs, self) };
+                let mut other_chunk = other.0;
+                let mut result = preferred.0;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(other_chunk, prime_index) {
+                    let prime = <$helpers_x>::PRIMES[index];
+                    if !<$helpers_x>::is_multiple(preferred.0, prime) {
+                        result = result.checked_mul(prime.saturating_pow(count))?;
+                    }
+                    other_chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                Some(Self(result, PhantomData))
+            }
+
             /// Try to create the difference (or complement) of this bag and `rhs`.
             /// Returns `None` if this bag is not a superset of `rhs`.
             /// The difference contains each element in the first bag a number of times equal to the number of times it appears in `self` minus the number of times it appears in `rhs`
+            /// This is multiset subtraction, so it can be used to remove a group of elements from the bag
+            #[doc(alias = "remove_all_of")]
             #[must_use]
             #[inline]
             pub const fn try_difference(&self, rhs: &Self) -> Option<Self> {
@@ -351,23 +1001,355 @@ macro_rules! prime_bag {
                 }
             }
 
-            /// Create the intersection of this bag and `rhs`.
-            /// The intersection contains each element which appears in both bags a number of times equal to the minimum number of times it appears in either bag.
-            #[must_use]
+            /// Try to create the difference (or complement) of this bag and `rhs`.
+            ///
+            /// # Errors
+            ///
+            /// Returns `NotASubset` if `rhs` is not a subset of this bag.
             #[inline]
-            pub const fn intersection(&self, rhs: &Self) -> Self {
-                let gcd = <$helpers_x>::gcd(self.0, rhs.0);
-                Self(gcd, PhantomData)
+            pub const fn try_difference_result(&self, rhs: &Self) -> Result<Self, PrimeBagError> {
+                match <$helpers_x>::div_exact(self.0, rhs.0) {
+                    Some(b) => Ok(Self(b, PhantomData)),
+                    None => Err(PrimeBagError::NotASubset),
+                }
             }
 
-            /// Returns the number of elements in the bag
-            /// You may want to use `is_count_at_least` instead
+            /// Try to create the difference (or complement) of this bag and `rhs`.
+            ///
+            /// # Errors
+            ///
+            /// If `rhs` is not a subset of this bag, returns the sub-bag of `rhs` which this bag lacks.
+            /// This contains each element a number of times equal to the number of times it appears in `rhs` minus the number of times it appears in `self`, or zero if that would be negative.
+            #[inline]
+            pub const fn try_difference_reporting(&self, rhs: &Self) -> Result<Self, Self> {
+                match <$helpers_x>::div_exact(self.0, rhs.0) {
+                    Some(b) => Ok(Self(b, PhantomData)),
+                    None => {
+                        let gcd = <$helpers_x>::gcd(self.0, rhs.0);
+                        match <$helpers_x>::div_exact(rhs.0, gcd) {
+                            Some(shortfall) => Err(Self(shortfall, PhantomData)),
+                            None => unreachable!(), // rhs is always a multiple of the gcd
+                        }
+                    }
+                }
+            }
+
+            /// Try to create the difference (or complement) of this bag and `rhs`.
+            ///
+            /// # Errors
+            ///
+            /// If `rhs` is not a subset of this bag, returns the saturating difference along with the shortfall.
+            /// The saturating difference contains each element a number of times equal to the number of times it appears in `self` minus the number of times it appears in `rhs`, or zero if that would be negative.
+            /// The shortfall is a list of `(prime_index, count)` pairs, in ascending order of prime index, giving how many more of each element `rhs` has than `self`.
+            #[cfg(feature = "alloc")]
+            pub fn difference_detailed(
+                &self,
+                rhs: &Self,
+            ) -> Result<Self, (Self, alloc::vec::Vec<(usize, u32)>)> {
+                let gcd = <$helpers_x>::gcd(self.0, rhs.0);
+                let Some(difference) = <$helpers_x>::div_exact(self.0, gcd) else {
+                    unreachable!() // self is always a multiple of the gcd
+                };
+                let Some(mut shortfall) = <$helpers_x>::div_exact(rhs.0, gcd) else {
+                    unreachable!() // rhs is always a multiple of the gcd
+                };
+                if shortfall == <$helpers_x>::ONE {
+                    return Ok(Self(difference, PhantomData));
+                }
+
+                let mut shortfalls = alloc::vec::Vec::new();
+                let mut prime_index = 0;
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(shortfall, prime_index) {
+                    shortfalls.push((index, count));
+                    shortfall = rest;
+                    prime_index = index + 1;
+                }
+                Err((Self(difference, PhantomData), shortfalls))
+            }
+
+            /// Try to create the complement of this bag within `universe`.
+            /// Returns `None` if this bag is not a subset of `universe`.
+            /// The complement contains each element in `universe` a number of times equal to the number of times it appears in `universe` minus the number of times it appears in `self`.
+            /// This is equivalent to `universe.try_difference(self)`
+            #[must_use]
+            #[inline]
+            pub const fn complement_in(&self, universe: &Self) -> Option<Self> {
+                universe.try_difference(self)
+            }
+
+            /// Create the intersection of this bag and `rhs`.
+            /// The intersection contains each element which appears in both bags a number of times equal to the minimum number of times it appears in either bag.
+            #[must_use]
+            #[inline]
+            pub const fn intersection(&self, rhs: &Self) -> Self {
+                if self.0.get() == <$helpers_x>::ONE.get() || rhs.0.get() == <$helpers_x>::ONE.get() {
+                    return Self::EMPTY;
+                }
+                let gcd = <$helpers_x>::gcd(self.0, rhs.0);
+                Self(gcd, PhantomData)
+            }
+
+            /// Try to create a bag containing only the elements which appear in both this bag and `rhs`,
+            /// each appearing a number of times equal to the maximum number of times it appears in either bag.
+            /// So elements are kept as in `intersection` but counted as in `try_union`.
+            /// e.g. the result for `[0, 0, 1]` and `[0, 2, 2]` is `[0, 0]`.
+            /// Returns `None` if the resulting bag would be too large.
+            #[must_use]
+            #[inline]
+            pub const fn try_union_over_intersection(&self, rhs: &Self) -> Option<Self> {
+                let mut common = <$helpers_x>::gcd(self.0, rhs.0);
+                let mut result = <$helpers_x>::ONE;
+                let mut prime_index = 0usize;
+
+                while let Some((index, _, rest)) = <$helpers_x>::next_group(common, prime_index) {
+                    let prime = <$helpers_x>::PRIMES[index];
+                    let lhs_power = Self::largest_power_dividing(self.0, prime);
+                    let rhs_power = Self::largest_power_dividing(rhs.0, prime);
+                    let power = if lhs_power.get() > rhs_power.get() {
+                        lhs_power
+                    } else {
+                        rhs_power
+                    };
+                    result = match result.checked_mul(power) {
+                        Some(r) => r,
+                        None => return None,
+                    };
+                    common = rest;
+                    prime_index = index + 1;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Returns the largest power of `prime` which divides `chunk`
+            const fn largest_power_dividing(chunk: $nonzero_ux, prime: $nonzero_ux) -> $nonzero_ux {
+                let mut power = <$helpers_x>::ONE;
+                while let Some(next) = power.checked_mul(prime) {
+                    if !<$helpers_x>::is_multiple(chunk, next) {
+                        break;
+                    }
+                    power = next;
+                }
+                power
+            }
+
+            /// Returns whether this bag and `rhs` have no elements in common.
+            /// This is true if and only if their intersection is empty.
+            #[must_use]
+            #[inline]
+            pub const fn is_disjoint(&self, rhs: &Self) -> bool {
+                if self.0.get() == <$helpers_x>::ONE.get() || rhs.0.get() == <$helpers_x>::ONE.get() {
+                    return true;
+                }
+                <$helpers_x>::gcd(self.0, rhs.0).get() == <$helpers_x>::ONE.get()
+            }
+
+            /// Create the intersection of this bag and `rhs`, keeping only whole blocks of `k` instances of each element.
+            /// Each element appears `k * floor(m / k)` times where `m` is the minimum number of times it appears in either bag.
+            /// For example, with `k = 2`, the intersection of `[a, a, a, b, b, c]` and `[a, a, a, b, b, b, c]` is `[a, a, b, b]`.
+            /// Returns an empty bag if `k` is zero.
+            #[must_use]
+            #[inline]
+            pub const fn intersection_floor(&self, rhs: &Self, k: u32) -> Self {
+                if k == 0 {
+                    return Self::EMPTY;
+                }
+                let mut chunk = <$helpers_x>::gcd(self.0, rhs.0);
+                let mut result = <$helpers_x>::ONE;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    let keep = count - (count % k);
+                    result = result.saturating_mul(<$helpers_x>::PRIMES[index].saturating_pow(keep));
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                Self(result, PhantomData)
+            }
+
+            /// Create the element-wise minimum of this bag and `rhs`.
+            /// This is an alias of `intersection`.
+            #[must_use]
+            #[inline]
+            pub const fn elementwise_min(&self, rhs: &Self) -> Self {
+                self.intersection(rhs)
+            }
+
+            /// Create the intersection of all of the `bags`.
+            /// Returns `None` if `bags` is empty.
+            /// Stops early if the intersection becomes empty.
+            #[must_use]
+            #[inline]
+            pub const fn intersection_of(bags: &[Self]) -> Option<Self> {
+                let [first, rest @ ..] = bags else {
+                    return None;
+                };
+                let mut result = first.0;
+                let mut index = 0;
+                while index < rest.len() {
+                    if result.get() == <$helpers_x>::ONE.get() {
+                        break;
+                    }
+                    result = <$helpers_x>::gcd(result, rest[index].0);
+                    index += 1;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Remove the intersection of the `bags` from each of them and return it.
+            /// After calling this, no element is common to all of the bags, although some of the bags may still share elements.
+            /// Returns an empty bag if `bags` is empty.
+            #[inline]
+            pub fn strip_common(bags: &mut [Self]) -> Self {
+                let Some(core) = Self::intersection_of(bags) else {
+                    return Self::EMPTY;
+                };
+                for bag in bags.iter_mut() {
+                    match bag.try_difference(&core) {
+                        Some(b) => *bag = b,
+                        None => unreachable!(), // every bag is a superset of the intersection
+                    }
+                }
+                core
+            }
+
+            /// Try to create the union of all of the `bags`.
+            /// Returns `None` if the resulting bag would be too large.
+            /// Returns an empty bag if `bags` is empty.
+            #[must_use]
+            #[inline]
+            pub const fn try_union_of(bags: &[Self]) -> Option<Self> {
+                let mut result = <$helpers_x>::ONE;
+                let mut index = 0;
+                while index < bags.len() {
+                    let Some(lcm) = <$helpers_x>::lcm(result, bags[index].0) else {
+                        return None;
+                    };
+                    result = lcm;
+                    index += 1;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Try to create the union of many bags, converting each of them into this bag type first.
+            /// This allows many narrow bags to be accumulated into a wider bag without overflowing at the narrower width.
+            /// Returns `None` only if the resulting bag would be too large for this bag type.
+            /// Returns an empty bag if `bags` is empty.
+            #[must_use]
+            #[inline]
+            pub fn try_union_all_into<B: Into<Self>, I: IntoIterator<Item = B>>(bags: I) -> Option<Self> {
+                let mut result = <$helpers_x>::ONE;
+                for bag in bags {
+                    result = <$helpers_x>::lcm(result, bag.into().0)?;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Returns the number of elements in the bag
+            /// You may want to use `is_count_at_least` instead
             #[inline]
             #[must_use]
             pub const fn count(&self) -> usize {
                 <$helpers_x>::count_chunk(self.0, 0)
             }
 
+            /// Returns the number of distinct elements in the bag
+            #[inline]
+            #[must_use]
+            pub const fn count_distinct(&self) -> usize {
+                <$helpers_x>::count_distinct_chunk(self.0)
+            }
+
+            /// Returns whether the bag contains exactly one kind of element, regardless of how many copies of it there are.
+            /// This is equivalent to `count_distinct() == 1` and is true if and only if the inner value is a prime power.
+            /// The empty bag does not contain one kind of element.
+            #[must_use]
+            #[inline]
+            pub const fn is_single_kind(&self) -> bool {
+                match <$helpers_x>::next_group(self.0, 0) {
+                    Some((_, _, rest)) => rest.get() == 1,
+                    None => false,
+                }
+            }
+
+            /// Returns a table giving, for each prime index, the maximum number of copies of that element which will fit in an otherwise empty bag.
+            /// The table has one entry for each prime index this bag can store and is computed at compile time.
+            #[inline]
+            #[must_use]
+            pub const fn max_count_table() -> &'static [u32] {
+                &<$helpers_x>::MAX_TOTAL_FOR_SINGLE_ELEMENT
+            }
+
+            /// Returns a mask with bit `i` set if the bag contains the element with prime index `i`.
+            /// Elements with prime index 128 or greater are not represented in the mask.
+            /// Compute this once and pass it to `contains_via_mask` to make repeated membership queries cheap.
+            #[inline]
+            #[must_use]
+            pub const fn present_mask(&self) -> u128 {
+                <$helpers_x>::present_mask_chunk(self.0)
+            }
+
+            /// Returns whether the bag contains the element with prime index `index`, using a `mask` from `present_mask`.
+            /// The `mask` must come from this bag, otherwise the result is meaningless.
+            /// Indices of 128 or greater are not represented in the mask so are checked against the bag directly.
+            #[inline]
+            #[must_use]
+            pub const fn contains_via_mask(&self, index: usize, mask: u128) -> bool {
+                if index < 128 {
+                    return (mask >> index) & 1 == 1;
+                }
+                match <$helpers_x>::get_prime(index) {
+                    Some(p) => <$helpers_x>::is_multiple(self.0, p),
+                    None => false,
+                }
+            }
+
+            /// Returns the number of ways to pick one distinct element from this bag and one distinct element from `rhs`.
+            /// This is the product of the distinct counts of the two bags, saturating at `usize::MAX`.
+            #[inline]
+            #[must_use]
+            pub const fn combined_distinct_count(&self, rhs: &Self) -> usize {
+                self.count_distinct().saturating_mul(rhs.count_distinct())
+            }
+
+            /// Returns the number of ways to pick one element instance from this bag and one element instance from `rhs`.
+            /// This is the product of the total counts of the two bags, saturating at `usize::MAX`.
+            #[inline]
+            #[must_use]
+            pub const fn combined_total_count(&self, rhs: &Self) -> usize {
+                self.count().saturating_mul(rhs.count())
+            }
+
+            /// Returns the number of elements in this bag which are not in `rhs`.
+            /// This is the sum over each element of its count in `self` minus its count in `rhs`, or zero if that would be negative.
+            /// Unlike `try_difference`, this does not require `self` to be a superset of `rhs`.
+            #[inline]
+            #[must_use]
+            pub const fn minus_count(&self, rhs: &Self) -> usize {
+                let gcd = <$helpers_x>::gcd(self.0, rhs.0);
+                match <$helpers_x>::div_exact(self.0, gcd) {
+                    Some(remainder) => <$helpers_x>::count_chunk(remainder, 0),
+                    None => unreachable!(), // self is always a multiple of the gcd
+                }
+            }
+
+            /// Returns the number of elements in the symmetric difference of this bag and `rhs`.
+            /// This is the sum over each element of the absolute difference between its count in `self` and its count in `rhs`.
+            /// This is the L1 distance between the count vectors of the two bags and does not require either bag to contain the other.
+            /// It is a metric: it is zero only for equal bags, it is symmetric and it satisfies the triangle inequality.
+            #[inline]
+            #[must_use]
+            pub const fn symmetric_difference_len(&self, rhs: &Self) -> usize {
+                let gcd = <$helpers_x>::gcd(self.0, rhs.0);
+                // Both bags are always multiples of the gcd
+                match (<$helpers_x>::div_exact(self.0, gcd), <$helpers_x>::div_exact(rhs.0, gcd)) {
+                    (Some(left), Some(right)) => {
+                        <$helpers_x>::count_chunk(left, 0) + <$helpers_x>::count_chunk(right, 0)
+                    }
+                    _ => unreachable!(),
+                }
+            }
+
             /// Returns whether the count is greater than or equal to `min`
             #[inline]
             #[must_use]
@@ -422,6 +1404,273 @@ macro_rules! prime_bag {
                 }
             }
 
+            /// Splits this bag into two bags which partition its distinct elements.
+            /// The first bag contains the first half of the distinct elements (rounded up) in prime index order and the second bag contains the rest.
+            /// Note that the split is by distinct element, not by total count - each element keeps all of its instances.
+            /// The sum of the two bags is always equal to this bag.
+            #[inline]
+            #[must_use]
+            pub const fn split_off_half(&self) -> (Self, Self) {
+                let distinct = self.dedup().count();
+                let mut remaining = distinct.div_ceil(2);
+                let mut chunk = self.0;
+                let mut first = <$helpers_x>::ONE;
+                let mut prime_index = 0;
+
+                while remaining > 0 {
+                    let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) else {
+                        core::debug_assert!(false, "Prime index is out of range");
+                        break;
+                    };
+                    first = first.saturating_mul(<$helpers_x>::PRIMES[index].saturating_pow(count));
+                    chunk = rest;
+                    prime_index = index + 1;
+                    remaining -= 1;
+                }
+
+                (Self(first, PhantomData), Self(chunk, PhantomData))
+            }
+
+            /// Returns an array giving the number of times each prime index appears in the bag.
+            /// The array has one entry for each prime index this bag can store.
+            /// The bag is only factorized once, so this is faster than calling `count_instances` for many elements.
+            #[must_use]
+            pub const fn count_all_indices(&self) -> [usize; <$helpers_x>::NUM_PRIMES] {
+                let mut result = [0usize; <$helpers_x>::NUM_PRIMES];
+                let mut chunk = self.0;
+                let mut prime_index = 0usize;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    result[index] = count as usize;
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                result
+            }
+
+            /// Returns a vector giving the number of times each prime index appears in the bag.
+            /// The vector has one entry for each prime index this bag can store.
+            #[cfg(feature = "alloc")]
+            #[must_use]
+            pub fn to_exponent_vec(&self) -> alloc::vec::Vec<u32> {
+                let mut result = alloc::vec![0u32; <$helpers_x>::NUM_PRIMES];
+                let mut chunk = self.0;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    result[index] = count;
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                result
+            }
+
+            /// Returns a map from each count to the prime indices of the elements which appear that many times.
+            /// The indices for each count are in ascending order.
+            /// e.g. a bag of `[1, 2, 2, 3, 3]` gives `{1: [1], 2: [2, 3]}`
+            #[cfg(feature = "alloc")]
+            #[must_use]
+            pub fn indices_by_count(
+                &self,
+            ) -> alloc::collections::BTreeMap<NonZeroUsize, alloc::vec::Vec<usize>> {
+                let mut result = alloc::collections::BTreeMap::<NonZeroUsize, alloc::vec::Vec<usize>>::new();
+                let mut chunk = self.0;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    if let Some(count) = NonZeroUsize::new(count as usize) {
+                        result.entry(count).or_default().push(index);
+                    }
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                result
+            }
+
+            /// Returns the prime indices of the elements in the bag, including repeats.
+            /// The indices are guaranteed to be in ascending order, so this is the inverse of `try_from_sorted_indices`.
+            #[cfg(feature = "alloc")]
+            #[must_use]
+            pub fn to_index_sorted_vec(&self) -> alloc::vec::Vec<usize> {
+                let mut result = alloc::vec::Vec::with_capacity(self.count());
+                let mut chunk = self.0;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    result.extend(core::iter::repeat(index).take(count as usize));
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                result
+            }
+
+            /// Try to create a bag from a slice of prime indices sorted in ascending order.
+            /// Each prime is looked up once for each run of equal indices.
+            /// Returns `None` if the bag would be too large or if any index is out of range.
+            /// If `indices` is not sorted, the result is still correct but slower to compute - sortedness is only a performance assumption.
+            #[must_use]
+            #[inline]
+            pub const fn try_from_sorted_indices(indices: &[usize]) -> Option<Self> {
+                let mut result = <$helpers_x>::ONE;
+                let mut index = 0usize;
+                let mut prime_index = usize::MAX;
+                let mut prime = <$helpers_x>::ONE;
+
+                while index < indices.len() {
+                    if indices[index] != prime_index {
+                        prime_index = indices[index];
+                        prime = match <$helpers_x>::get_prime(prime_index) {
+                            Some(p) => p,
+                            None => return None,
+                        };
+                    }
+                    result = match result.checked_mul(prime) {
+                        Some(r) => r,
+                        None => return None,
+                    };
+                    index += 1;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Try to create a bag from a slice giving the number of times each prime index appears.
+            /// This is the inverse of `to_exponent_vec`.
+            /// Returns `None` if the bag would be too large or if a prime index with a nonzero count is out of range.
+            #[must_use]
+            pub fn try_from_exponent_slice(exponents: &[u32]) -> Option<Self> {
+                let mut result = <$helpers_x>::ONE;
+
+                for (prime_index, exponent) in exponents.iter().enumerate() {
+                    if *exponent == 0 {
+                        continue;
+                    }
+                    let p = <$helpers_x>::get_prime(prime_index)?.checked_pow(*exponent)?;
+                    result = result.checked_mul(p)?;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Returns a map from each prime index in the bag to the number of times it appears.
+            /// Keys are prime indices so the element type does not need to implement `Hash`.
+            /// Use `try_from_count_map` to convert the map back into a bag.
+            #[cfg(feature = "std")]
+            #[must_use]
+            pub fn to_count_map(&self) -> std::collections::HashMap<usize, usize> {
+                let mut result = std::collections::HashMap::new();
+                let mut chunk = self.0;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    result.insert(index, count as usize);
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                result
+            }
+
+            /// Try to create a bag from a map from prime indices to the number of times they appear.
+            /// This is the inverse of `to_count_map`.
+            /// Returns `None` if the bag would be too large or if a prime index with a nonzero count is out of range.
+            #[cfg(feature = "std")]
+            #[must_use]
+            pub fn try_from_count_map<S: core::hash::BuildHasher>(
+                map: &std::collections::HashMap<usize, usize, S>,
+            ) -> Option<Self> {
+                let mut result = <$helpers_x>::ONE;
+
+                for (prime_index, count) in map {
+                    if *count == 0 {
+                        continue;
+                    }
+                    let exponent = u32::try_from(*count).ok()?;
+                    let p = <$helpers_x>::get_prime(*prime_index)?.checked_pow(exponent)?;
+                    result = result.checked_mul(p)?;
+                }
+                Some(Self(result, PhantomData))
+            }
+
+            /// Returns the first prime index present in the bag which satisfies `pred`.
+            /// Indices are checked in ascending order and each distinct index is checked once.
+            /// This avoids constructing elements, the caller can use `PrimeBagElement::from_prime_index` if needed.
+            #[inline]
+            #[must_use]
+            pub fn find_index<F: FnMut(usize) -> bool>(&self, mut pred: F) -> Option<usize> {
+                let mut chunk = self.0;
+                let mut prime_index = 0;
+
+                while let Some((index, _, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    if pred(index) {
+                        return Some(index);
+                    }
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+                None
+            }
+
+            /// Try to create a new bag by moving every element to the prime index given by `f`.
+            /// Each element keeps its count and elements which are mapped to the same index have their counts summed.
+            /// This can be used to migrate bags after changing the assignment of prime indices.
+            /// Returns `None` if the resulting bag would be too large or if `f` returns an index which is out of range.
+            #[inline]
+            #[must_use]
+            pub fn try_remap<F: FnMut(usize) -> usize>(&self, mut f: F) -> Option<Self> {
+                let mut chunk = self.0;
+                let mut result = <$helpers_x>::ONE;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    let new_prime = <$helpers_x>::get_prime(f(index))?;
+                    result = result.checked_mul(new_prime.checked_pow(count)?)?;
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                Some(Self(result, PhantomData))
+            }
+
+            /// Returns a copy of this bag containing only the elements for which `f` returns true.
+            /// `f` is called once for each distinct element with its prime index and count.
+            /// This avoids constructing elements.
+            #[inline]
+            #[must_use]
+            pub fn filter_indices<F: FnMut(usize, NonZeroUsize) -> bool>(&self, mut f: F) -> Self {
+                let mut chunk = self.0;
+                let mut result = <$helpers_x>::ONE;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    if let Some(count_nonzero) = NonZeroUsize::new(count as usize) {
+                        if f(index, count_nonzero) {
+                            result = result.saturating_mul(<$helpers_x>::PRIMES[index].saturating_pow(count));
+                        }
+                    }
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                Self(result, PhantomData)
+            }
+
+            /// Returns the sum over each element in the bag of its weight multiplied by its count.
+            /// `weight` is called once for each distinct element with its prime index.
+            /// Weights may be negative. The calculation saturates at the bounds of `i64`.
+            #[inline]
+            #[must_use]
+            pub fn weighted_total<F: FnMut(usize) -> i64>(&self, mut weight: F) -> i64 {
+                let mut chunk = self.0;
+                let mut total = 0i64;
+                let mut prime_index = 0;
+
+                while let Some((index, count, rest)) = <$helpers_x>::next_group(chunk, prime_index) {
+                    total = total.saturating_add(weight(index).saturating_mul(i64::from(count)));
+                    chunk = rest;
+                    prime_index = index + 1;
+                }
+
+                total
+            }
+
             /// Returns a copy of `self` with duplicate items removed
             #[inline]
             #[must_use]
@@ -474,25 +1723,243 @@ prime_bag!(PrimeBag32, Helpers32, NonZeroU32, u32);
 prime_bag!(PrimeBag64, Helpers64, NonZeroU64, u64);
 prime_bag!(PrimeBag128, Helpers128, NonZeroU128, u128);
 
+/// Marker type used to select a bag by the number of bits in its backing integer
+pub struct Bits<const BITS: usize>;
+
+/// Maps a number of bits to the bag type whose backing integer has that many bits
+pub trait BagForBits {
+    /// The bag type
+    type Bag<E>;
+}
+
+impl BagForBits for Bits<8> {
+    type Bag<E> = PrimeBag8<E>;
+}
+impl BagForBits for Bits<16> {
+    type Bag<E> = PrimeBag16<E>;
+}
+impl BagForBits for Bits<32> {
+    type Bag<E> = PrimeBag32<E>;
+}
+impl BagForBits for Bits<64> {
+    type Bag<E> = PrimeBag64<E>;
+}
+impl BagForBits for Bits<128> {
+    type Bag<E> = PrimeBag128<E>;
+}
+
+/// A bag of `IndexElement` backed by a `u8`
+pub type PrimeBagIndices8 = PrimeBag8<IndexElement>;
+/// A bag of `IndexElement` backed by a `u16`
+pub type PrimeBagIndices16 = PrimeBag16<IndexElement>;
+/// A bag of `IndexElement` backed by a `u32`
+pub type PrimeBagIndices32 = PrimeBag32<IndexElement>;
+/// A bag of `IndexElement` backed by a `u64`
+pub type PrimeBagIndices64 = PrimeBag64<IndexElement>;
+/// A bag of `IndexElement` backed by a `u128`
+pub type PrimeBagIndices128 = PrimeBag128<IndexElement>;
+
+/// The bag type whose backing integer has `BITS` bits
+/// e.g. `PrimeBagFor<E, 32>` is `PrimeBag32<E>`
+pub type PrimeBagFor<E, const BITS: usize> = <Bits<BITS> as BagForBits>::Bag<E>;
+
+/// Operations common to bags of every size.
+/// This allows writing code which is generic over the size of bag.
+pub trait BagOps: Sized {
+    /// The type of element in the bag
+    type Elem;
+
+    /// The empty bag
+    const EMPTY: Self;
+
+    /// Try to create a new bag with the `value` inserted.
+    /// Returns `None` if the bag does not have enough space.
+    #[must_use]
+    fn try_insert(&self, value: Self::Elem) -> Option<Self>;
+
+    /// Try to remove `value` from this bag.
+    /// Returns `None` if the bag does not contain `value`.
+    #[must_use]
+    fn try_remove(&self, value: Self::Elem) -> Option<Self>;
+
+    /// Returns whether the bag contains a particular `value`.
+    #[must_use]
+    fn contains(&self, value: Self::Elem) -> bool;
+
+    /// Returns the number of instances of `value` in the bag.
+    #[must_use]
+    fn count_instances(&self, value: Self::Elem) -> usize;
+
+    /// Returns the number of elements in the bag.
+    #[must_use]
+    fn count(&self) -> usize;
+
+    /// Returns whether the bag is empty.
+    #[must_use]
+    fn is_empty(&self) -> bool;
+
+    /// Returns whether this bag is a superset of `rhs`.
+    #[must_use]
+    fn is_superset(&self, rhs: &Self) -> bool;
+
+    /// Try to create the sum of this bag and `rhs`.
+    /// Returns `None` if the resulting bag would be too large.
+    #[must_use]
+    fn try_sum(&self, rhs: &Self) -> Option<Self>;
+
+    /// Try to create the union of this bag and `rhs`.
+    /// Returns `None` if the resulting bag would be too large.
+    #[must_use]
+    fn try_union(&self, rhs: &Self) -> Option<Self>;
+
+    /// Try to create the difference of this bag and `rhs`.
+    /// Returns `None` if this bag is not a superset of `rhs`.
+    #[must_use]
+    fn try_difference(&self, rhs: &Self) -> Option<Self>;
+
+    /// Create the intersection of this bag and `rhs`.
+    #[must_use]
+    fn intersection(&self, rhs: &Self) -> Self;
+}
+
+macro_rules! bag_ops {
+    ($bag_x: ident) => {
+        impl<E: PrimeBagElement> BagOps for $bag_x<E> {
+            type Elem = E;
+
+            const EMPTY: Self = $bag_x::EMPTY;
+
+            #[inline]
+            fn try_insert(&self, value: E) -> Option<Self> {
+                $bag_x::try_insert(self, value)
+            }
+
+            #[inline]
+            fn try_remove(&self, value: E) -> Option<Self> {
+                $bag_x::try_remove(self, value)
+            }
+
+            #[inline]
+            fn contains(&self, value: E) -> bool {
+                $bag_x::contains(self, value)
+            }
+
+            #[inline]
+            fn count_instances(&self, value: E) -> usize {
+                $bag_x::count_instances(self, value)
+            }
+
+            #[inline]
+            fn count(&self) -> usize {
+                $bag_x::count(self)
+            }
+
+            #[inline]
+            fn is_empty(&self) -> bool {
+                $bag_x::is_empty(self)
+            }
+
+            #[inline]
+            fn is_superset(&self, rhs: &Self) -> bool {
+                $bag_x::is_superset(self, rhs)
+            }
+
+            #[inline]
+            fn try_sum(&self, rhs: &Self) -> Option<Self> {
+                $bag_x::try_sum(self, rhs)
+            }
+
+            #[inline]
+            fn try_union(&self, rhs: &Self) -> Option<Self> {
+                $bag_x::try_union(self, rhs)
+            }
+
+            #[inline]
+            fn try_difference(&self, rhs: &Self) -> Option<Self> {
+                $bag_x::try_difference(self, rhs)
+            }
+
+            #[inline]
+            fn intersection(&self, rhs: &Self) -> Self {
+                $bag_x::intersection(self, rhs)
+            }
+        }
+    };
+}
+
+bag_ops!(PrimeBag8);
+bag_ops!(PrimeBag16);
+bag_ops!(PrimeBag32);
+bag_ops!(PrimeBag64);
+bag_ops!(PrimeBag128);
+
 macro_rules! into_iterator {
-    ($bag_x: ty, $iter_x: ty) => {
+    ($bag_x: ty, $iter_x: ty, $clone_iter_x: ty, $index_iter_x: ty) => {
         impl<E: PrimeBagElement> IntoIterator for $bag_x {
             type Item = E;
             type IntoIter = $iter_x;
 
+            /// Iterate through the elements of the bag.
+            /// Elements are guaranteed to be returned in ascending order of prime index, with repeated elements adjacent.
             #[inline]
             fn into_iter(self) -> Self::IntoIter {
                 Self::IntoIter::new(self.0)
             }
         }
+
+        impl<E: PrimeBagElement + Clone> $bag_x {
+            /// Iterate through the elements of the bag, calling `from_prime_index` once for each distinct element and cloning it for repeats.
+            /// Elements are returned in the same order as `into_iter`.
+            /// Use this instead of `into_iter` if `from_prime_index` is expensive.
+            #[inline]
+            pub fn iter_unique_then_repeat(&self) -> impl ExactSizeIterator<Item = E> {
+                <$clone_iter_x>::new(self.0)
+            }
+        }
+
+        impl<E> $bag_x {
+            /// Iterate through the prime indices of the elements of the bag.
+            /// Indices are returned in ascending order with repeats, the same order as `into_iter`.
+            /// This never calls `from_prime_index` so it is useful when the elements are only needed to look up a value by index,
+            /// e.g. `bag.into_index_iter().map(|i| SCORES[i]).sum()`
+            #[inline]
+            pub fn into_index_iter(self) -> impl DoubleEndedIterator<Item = usize> {
+                <$index_iter_x>::new(self.0).map(|e| e.0)
+            }
+        }
     };
 }
 
-into_iterator!(PrimeBag8<E>, PrimeBagIter8<E>);
-into_iterator!(PrimeBag16<E>, PrimeBagIter16<E>);
-into_iterator!(PrimeBag32<E>, PrimeBagIter32<E>);
-into_iterator!(PrimeBag64<E>, PrimeBagIter64<E>);
-into_iterator!(PrimeBag128<E>, PrimeBagIter128<E>);
+into_iterator!(
+    PrimeBag8<E>,
+    PrimeBagIter8<E>,
+    PrimeBagCloneIter8<E>,
+    PrimeBagIter8<IndexElement>
+);
+into_iterator!(
+    PrimeBag16<E>,
+    PrimeBagIter16<E>,
+    PrimeBagCloneIter16<E>,
+    PrimeBagIter16<IndexElement>
+);
+into_iterator!(
+    PrimeBag32<E>,
+    PrimeBagIter32<E>,
+    PrimeBagCloneIter32<E>,
+    PrimeBagIter32<IndexElement>
+);
+into_iterator!(
+    PrimeBag64<E>,
+    PrimeBagIter64<E>,
+    PrimeBagCloneIter64<E>,
+    PrimeBagIter64<IndexElement>
+);
+into_iterator!(
+    PrimeBag128<E>,
+    PrimeBagIter128<E>,
+    PrimeBagCloneIter128<E>,
+    PrimeBagIter128<IndexElement>
+);
 
 macro_rules! from_bag_to_bag {
     ($t_from: ty, $t_into: ty) => {
@@ -520,23 +1987,57 @@ from_bag_to_bag!(PrimeBag32<E>, PrimeBag128<E>);
 from_bag_to_bag!(PrimeBag64<E>, PrimeBag128<E>);
 
 macro_rules! group_iterator {
-    ($bag_x: ty, $iter_x: ty) => {
+    ($bag_x: ty, $iter_x: ty, $rest_iter_x: ty) => {
         impl<E: PrimeBagElement> $bag_x {
             /// Iterate through groups of elements, each item of the iterator will be the element and its count.
             /// Elements which are not present are skipped.
+            /// Groups are guaranteed to be returned in ascending order of prime index, which is the same order as `into_iter`.
+            /// The number of distinct elements is calculated when the iterator is created so `len` is cheap.
             #[inline]
-            pub fn iter_groups(&self) -> impl Iterator<Item = (E, NonZeroUsize)> {
+            pub fn iter_groups(
+                &self,
+            ) -> impl ExactSizeIterator<Item = (E, NonZeroUsize)> + DoubleEndedIterator {
                 <$iter_x>::new(self.0)
             }
+
+            /// Iterate through groups of elements, each item of the iterator will be the element and its count along with the rest of the bag.
+            /// The rest of the bag is this bag with that group and all previous groups removed.
+            /// Elements which are not present are skipped.
+            #[inline]
+            pub fn iter_groups_with_rest(
+                &self,
+            ) -> impl ExactSizeIterator<Item = ((E, NonZeroUsize), Self)> {
+                <$rest_iter_x>::new(self.0)
+            }
         }
     };
 }
 
-group_iterator!(PrimeBag8<E>, PrimeBagGroupIter8<E>);
-group_iterator!(PrimeBag16<E>, PrimeBagGroupIter16<E>);
-group_iterator!(PrimeBag32<E>, PrimeBagGroupIter32<E>);
-group_iterator!(PrimeBag64<E>, PrimeBagGroupIter64<E>);
-group_iterator!(PrimeBag128<E>, PrimeBagGroupIter128<E>);
+group_iterator!(
+    PrimeBag8<E>,
+    PrimeBagGroupIter8<E>,
+    PrimeBagGroupRestIter8<E>
+);
+group_iterator!(
+    PrimeBag16<E>,
+    PrimeBagGroupIter16<E>,
+    PrimeBagGroupRestIter16<E>
+);
+group_iterator!(
+    PrimeBag32<E>,
+    PrimeBagGroupIter32<E>,
+    PrimeBagGroupRestIter32<E>
+);
+group_iterator!(
+    PrimeBag64<E>,
+    PrimeBagGroupIter64<E>,
+    PrimeBagGroupRestIter64<E>
+);
+group_iterator!(
+    PrimeBag128<E>,
+    PrimeBagGroupIter128<E>,
+    PrimeBagGroupRestIter128<E>
+);
 
 #[cfg(test)]
 mod tests {
@@ -552,6 +2053,410 @@ mod tests {
         }
     }
 
+    /// Fills 100 bags by inserting random elements with prime indices in `0..max_index` until each bag is full.
+    /// Returns every bag created while filling, from the empty bag to the full bag.
+    /// If `bias_zero` is set then half of the insertions are of element 0, to exercise the trailing zeros logic.
+    pub(crate) fn random_fillings<B: BagOps<Elem = usize> + Copy>(
+        max_index: usize,
+        bias_zero: bool,
+    ) -> Vec<Vec<B>> {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        (0..100)
+            .map(|_| {
+                let mut bag = B::EMPTY;
+                let mut filling = vec![bag];
+                loop {
+                    let value = if bias_zero && rng.gen_bool(0.5) {
+                        0
+                    } else {
+                        rng.gen_range(0..max_index)
+                    };
+                    match bag.try_insert(value) {
+                        Some(new_bag) => {
+                            bag = new_bag;
+                            filling.push(bag);
+                        }
+                        None => return filling,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The full bags from `random_fillings`
+    pub(crate) fn random_full_bags<B: BagOps<Elem = usize> + Copy>(
+        max_index: usize,
+        bias_zero: bool,
+    ) -> Vec<B> {
+        random_fillings(max_index, bias_zero)
+            .into_iter()
+            .map(|filling| filling[filling.len() - 1])
+            .collect()
+    }
+
+    /// Creates `count` bags, each with up to `max_len` random elements with prime indices in `0..max_index`.
+    /// Bags which would be too large are skipped.
+    pub(crate) fn random_small_bags<B: BagOps<Elem = usize>>(
+        count: usize,
+        max_len: usize,
+        max_index: usize,
+    ) -> Vec<B> {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        (0..count)
+            .filter_map(|_| {
+                let len = rng.gen_range(0..=max_len);
+                (0..len).try_fold(B::EMPTY, |bag, _| {
+                    bag.try_insert(rng.gen_range(0..max_index))
+                })
+            })
+            .collect()
+    }
+
+    /// Runs `$body` once for each size of bag, with `$bag` as a generic alias of that size of bag
+    macro_rules! for_each_width {
+        ($bag: ident => $body: block) => {{
+            {
+                type $bag<E> = PrimeBag8<E>;
+                $body
+            }
+            {
+                type $bag<E> = PrimeBag16<E>;
+                $body
+            }
+            {
+                type $bag<E> = PrimeBag32<E>;
+                $body
+            }
+            {
+                type $bag<E> = PrimeBag64<E>;
+                $body
+            }
+            {
+                type $bag<E> = PrimeBag128<E>;
+                $body
+            }
+        }};
+    }
+    pub(crate) use for_each_width;
+
+    #[test]
+    fn test_layout() {
+        macro_rules! test_layout {
+            ($bag_x: ident, $nonzero_ux: ty, $ux: ty) => {
+                assert_eq!(
+                    core::mem::size_of::<$bag_x<usize>>(),
+                    core::mem::size_of::<$nonzero_ux>()
+                );
+                assert_eq!(
+                    core::mem::align_of::<$bag_x<usize>>(),
+                    core::mem::align_of::<$nonzero_ux>()
+                );
+                assert_eq!(
+                    core::mem::size_of::<[$bag_x<usize>; 3]>(),
+                    core::mem::size_of::<[$ux; 3]>()
+                );
+                assert_eq!(
+                    core::mem::size_of::<Option<$bag_x<usize>>>(),
+                    core::mem::size_of::<$ux>()
+                );
+            };
+        }
+
+        test_layout!(PrimeBag8, NonZeroU8, u8);
+        test_layout!(PrimeBag16, NonZeroU16, u16);
+        test_layout!(PrimeBag32, NonZeroU32, u32);
+        test_layout!(PrimeBag64, NonZeroU64, u64);
+        test_layout!(PrimeBag128, NonZeroU128, u128);
+    }
+
+    #[test]
+    fn test_insertion_order_independence() {
+        use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let mut elements: Vec<usize> = bag.into_iter().collect();
+
+                for _ in 0..5 {
+                    elements.shuffle(&mut rng);
+                    let shuffled = Bag::<usize>::try_from_iter(elements.iter().copied()).unwrap();
+                    assert_eq!(shuffled.into_inner(), bag.into_inner());
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_iter_unique_then_repeat() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        static CONSTRUCTED: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Clone, PartialEq)]
+        struct Counted(usize);
+
+        impl PrimeBagElement for Counted {
+            fn to_prime_index(&self) -> usize {
+                self.0
+            }
+
+            fn from_prime_index(value: usize) -> Self {
+                CONSTRUCTED.fetch_add(1, Ordering::Relaxed);
+                Self(value)
+            }
+        }
+
+        let bag = PrimeBag64::<Counted>::try_from_iter([0, 0, 0, 1, 3, 3, 5].map(Counted)).unwrap();
+
+        let iter = bag.iter_unique_then_repeat();
+        assert_eq!(iter.len(), 7);
+        let actual: Vec<_> = iter.collect();
+        assert_eq!(CONSTRUCTED.swap(0, Ordering::Relaxed), 4);
+
+        let expected: Vec<_> = bag.into_iter().collect();
+        assert_eq!(CONSTRUCTED.swap(0, Ordering::Relaxed), 7);
+        assert_eq!(actual, expected);
+
+        assert_eq!(
+            PrimeBag64::<Counted>::EMPTY
+                .iter_unique_then_repeat()
+                .next(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_iter_unique_then_repeat_random() {
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let mut iter = bag.iter_unique_then_repeat();
+                let mut expected = bag.into_iter();
+                loop {
+                    assert_eq!(iter.len(), expected.clone().count());
+                    let next = iter.next();
+                    assert_eq!(next, expected.next());
+                    if next.is_none() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_debug_check_element_roundtrip() {
+        for index in 0..32 {
+            debug_check_element_roundtrip::<usize>(index);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not map back")]
+    fn test_debug_check_element_roundtrip_broken() {
+        struct Broken(usize);
+
+        impl PrimeBagElement for Broken {
+            fn to_prime_index(&self) -> usize {
+                self.0 / 2
+            }
+
+            fn from_prime_index(value: usize) -> Self {
+                Self(value)
+            }
+        }
+
+        debug_check_element_roundtrip::<Broken>(0);
+        debug_check_element_roundtrip::<Broken>(3);
+    }
+
+    #[test]
+    fn test_bag_ops() {
+        fn fill<B: BagOps<Elem = usize>>(values: &[usize]) -> Option<B> {
+            let mut bag = B::EMPTY;
+            for value in values {
+                bag = bag.try_insert(*value)?;
+            }
+            Some(bag)
+        }
+
+        fn check<B: BagOps<Elem = usize> + PartialEq + Debug>() {
+            let bag: B = fill(&[0, 1, 1]).unwrap();
+            let other: B = fill(&[1, 2]).unwrap();
+
+            assert!(B::EMPTY.is_empty());
+            assert!(!bag.is_empty());
+            assert_eq!(bag.count(), 3);
+            assert_eq!(bag.count_instances(1), 2);
+            assert!(bag.contains(0));
+            assert!(!bag.contains(2));
+            assert_eq!(bag.try_remove(1), fill(&[0, 1]));
+            assert_eq!(bag.try_remove(2), None);
+            assert!(bag.is_superset(&fill(&[1, 1]).unwrap()));
+            assert_eq!(bag.try_sum(&other), fill(&[0, 1, 1, 1, 2]));
+            assert_eq!(bag.try_union(&other), fill(&[0, 1, 1, 2]));
+            assert_eq!(bag.try_difference(&fill(&[1]).unwrap()), fill(&[0, 1]));
+            assert_eq!(bag.try_difference(&other), None);
+            assert_eq!(Some(bag.intersection(&other)), fill(&[1]));
+        }
+
+        check::<PrimeBag16<usize>>();
+        check::<PrimeBag32<usize>>();
+        check::<PrimeBag64<usize>>();
+        check::<PrimeBag128<usize>>();
+
+        assert_eq!(
+            fill::<PrimeBag8<usize>>(&[0, 1, 1]),
+            PrimeBag8::try_from_iter([0, 1, 1])
+        );
+        assert_eq!(fill::<PrimeBag8<usize>>(&[4, 4, 4]), None);
+    }
+
+    #[test]
+    fn test_into_index_iter() {
+        const SCORES: [u32; 6] = [1, 3, 3, 2, 1, 4];
+        let bag = PrimeBag32::<usize>::try_from_iter([5, 2, 0, 2]).unwrap();
+        assert_eq!(bag.into_index_iter().collect::<Vec<_>>(), [0, 2, 2, 5]);
+        assert_eq!(
+            bag.into_index_iter().rev().collect::<Vec<_>>(),
+            [5, 2, 2, 0]
+        );
+
+        let score: u32 = bag.into_index_iter().map(|i| SCORES[i]).sum();
+        assert_eq!(score, 1 + 3 + 3 + 4);
+
+        assert_eq!(PrimeBag32::<usize>::EMPTY.into_index_iter().next(), None);
+    }
+
+    #[test]
+    fn test_iteration_order() {
+        let bag = PrimeBag32::<usize>::try_from_iter([5, 2, 0, 2]).unwrap();
+
+        let elements: Vec<_> = bag.into_iter().collect();
+        assert_eq!(elements, [0, 2, 2, 5]);
+
+        let groups: Vec<_> = bag.iter_groups().map(|(e, c)| (e, c.get())).collect();
+        assert_eq!(groups, [(0, 1), (2, 2), (5, 1)]);
+
+        let elements: Vec<_> = bag.iter_unique_then_repeat().collect();
+        assert_eq!(elements, [0, 2, 2, 5]);
+    }
+
+    #[test]
+    fn test_iter_groups_reverse() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 0, 0, 3, 3, 7]).unwrap();
+        let reversed: Vec<_> = bag.iter_groups().rev().map(|(e, c)| (e, c.get())).collect();
+        assert_eq!(reversed, [(7, 1), (3, 2), (0, 3)]);
+
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 0]).unwrap();
+        let reversed: Vec<_> = bag.iter_groups().rev().map(|(e, c)| (e, c.get())).collect();
+        assert_eq!(reversed, [(0, 2)]);
+
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, true) {
+                let mut forward: Vec<_> = bag.iter_groups().collect();
+                forward.reverse();
+                let backward: Vec<_> = bag.iter_groups().rev().collect();
+                assert_eq!(forward, backward);
+
+                let mut expected: std::collections::VecDeque<_> = bag.iter_groups().collect();
+                let mut iter = bag.iter_groups();
+                loop {
+                    assert_eq!(iter.len(), expected.len());
+                    let (e, a) = if rng.gen_bool(0.5) {
+                        (expected.pop_front(), iter.next())
+                    } else {
+                        (expected.pop_back(), iter.next_back())
+                    };
+                    assert_eq!(e, a);
+                    if e.is_none() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_iter_groups_element_zero() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 0, 0]).unwrap();
+        assert_eq!(bag.into_inner().get(), 16);
+        let groups: Vec<_> = bag.iter_groups().collect();
+        assert_eq!(groups, [(0, NonZeroUsize::new(4).unwrap())]);
+
+        let (full, _) = PrimeBag8::<usize>::EMPTY.fill_with(0);
+        let groups: Vec<_> = full.iter_groups().collect();
+        assert_eq!(groups, [(0, NonZeroUsize::new(7).unwrap())]);
+
+        let bag = PrimeBag128::<usize>::try_from_iter([0, 0, 0, 1, 5, 5]).unwrap();
+        let groups: Vec<_> = bag.iter_groups().collect();
+        assert_eq!(
+            groups,
+            [
+                (0, NonZeroUsize::new(3).unwrap()),
+                (1, NonZeroUsize::new(1).unwrap()),
+                (5, NonZeroUsize::new(2).unwrap())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_groups_random() {
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(4, false) {
+                let mut iter = bag.iter_groups();
+                assert_eq!(iter.len(), bag.count_distinct());
+                let mut rebuilt = Bag::<usize>::EMPTY;
+                while let Some((element, count)) = iter.next() {
+                    assert_eq!(count.get(), bag.count_instances(element));
+                    rebuilt = rebuilt
+                        .try_extend(core::iter::repeat_n(element, count.get()))
+                        .unwrap();
+                    assert_eq!(iter.len(), bag.count_distinct() - rebuilt.count_distinct());
+                }
+                assert_eq!(rebuilt, bag);
+            }
+        });
+    }
+
+    #[test]
+    fn test_lossy_into_u16() {
+        let bag = PrimeBag128::<usize>::try_from_iter([0, 0, 1, 2, 2, 2, 3, 4]).unwrap();
+        let (small, rest) = bag.lossy_into_u16();
+        //Note: 2 * 2 * 3 * 5 * 5 * 5 * 7 fits in a u16 but multiplying by 11 would not
+        assert_eq!(
+            small,
+            PrimeBag16::try_from_iter([0, 0, 1, 2, 2, 2, 3]).unwrap()
+        );
+        assert_eq!(rest, PrimeBag128::try_from_iter([4]).unwrap());
+        assert_eq!(PrimeBag128::from(small).try_sum(&rest), Some(bag));
+
+        let bag = PrimeBag128::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        assert_eq!(
+            bag.lossy_into_u16(),
+            (
+                PrimeBag16::try_from_iter([0, 1, 2]).unwrap(),
+                PrimeBag128::EMPTY
+            )
+        );
+
+        let bag = PrimeBag8::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        assert_eq!(
+            bag.lossy_into_u16(),
+            (
+                PrimeBag16::try_from_iter([0, 1, 2]).unwrap(),
+                PrimeBag8::EMPTY
+            )
+        );
+    }
+
     #[test]
     fn test_inner() {
         let bag = PrimeBag8::<usize>::try_from_iter([1, 1, 2]).unwrap();
@@ -559,6 +2464,13 @@ mod tests {
         let inner = bag.into_inner();
 
         assert_eq!(inner.get(), 45);
+        assert_eq!(bag.into_inner_primitive(), 45u8);
+        assert_eq!(PrimeBag8::from_primitive(45), Some(bag));
+        assert_eq!(PrimeBag8::<usize>::from_primitive(0), None);
+        assert_eq!(
+            PrimeBag128::<usize>::from_primitive(u128::MAX).map(PrimeBag128::into_inner_primitive),
+            Some(u128::MAX)
+        );
 
         let bag = PrimeBag8::<usize>::from_inner(NonZeroU8::new(45).unwrap());
 
@@ -573,6 +2485,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_inner_checked() {
+        let bag = PrimeBag8::<usize>::from_inner_checked(NonZeroU8::new(45).unwrap());
+        assert_eq!(
+            bag,
+            Ok(PrimeBag8::<usize>::try_from_iter([1, 1, 2]).unwrap())
+        );
+        assert_eq!(
+            PrimeBag8::<usize>::from_inner_checked(NonZeroU8::MIN),
+            Ok(PrimeBag8::EMPTY)
+        );
+
+        let bag: Result<PrimeBag16<usize>, _> = NonZeroU16::new(4 * 131).unwrap().try_into();
+        assert_eq!(
+            bag,
+            Ok(PrimeBag16::<usize>::try_from_iter([0, 0, 31]).unwrap())
+        );
+
+        #[cfg(not(feature = "primes256"))]
+        {
+            let bag: Result<PrimeBag16<usize>, _> = NonZeroU16::new(137).unwrap().try_into();
+            assert_eq!(bag, Err(PrimeBagError::IndexOutOfRange));
+        }
+
+        let too_big = NonZeroU128::new(3 * 2003).unwrap();
+        assert_eq!(
+            PrimeBag128::<usize>::try_from(too_big),
+            Err(PrimeBagError::IndexOutOfRange)
+        );
+    }
+
     #[test]
     fn test_iter_groups_8() {
         let bag = PrimeBag8::<usize>::try_from_iter([1, 1, 2]).unwrap();
@@ -632,18 +2575,77 @@ mod tests {
     }
 
     #[test]
-    fn test_iter_groups_128() {
-        let bag = PrimeBag128::<usize>::try_from_iter([1, 1, 1, 3, 3, 4, 4, 4]).unwrap();
-        let v: Vec<_> = bag.iter_groups().collect();
+    fn test_iter_groups_128() {
+        let bag = PrimeBag128::<usize>::try_from_iter([1, 1, 1, 3, 3, 4, 4, 4]).unwrap();
+        let v: Vec<_> = bag.iter_groups().collect();
+
+        assert_eq!(
+            v,
+            [
+                (1, NonZeroUsize::new(3).unwrap()),
+                (3, NonZeroUsize::new(2).unwrap()),
+                (4, NonZeroUsize::new(3).unwrap())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_groups_len() {
+        let bag = PrimeBag64::<usize>::try_from_iter([0, 0, 1, 3, 3, 3, 7]).unwrap();
+        let mut iter = bag.iter_groups();
+
+        for expected_len in (0..=4).rev() {
+            assert_eq!(iter.len(), expected_len);
+            assert_eq!(iter.size_hint(), (expected_len, Some(expected_len)));
+            assert_eq!(iter.next().is_some(), expected_len > 0);
+        }
+
+        assert_eq!(PrimeBag64::<usize>::EMPTY.iter_groups().len(), 0);
+    }
+
+    #[test]
+    fn test_prime_bag_for() {
+        let bag: PrimeBagFor<usize, 16> =
+            PrimeBagFor::<usize, 16>::try_from_iter([1, 2, 3]).unwrap();
+        assert_eq!(bag, PrimeBag16::<usize>::try_from_iter([1, 2, 3]).unwrap());
+
+        assert_eq_size!(PrimeBagFor<usize, 8>, u8);
+        assert_eq_size!(PrimeBagFor<usize, 16>, u16);
+        assert_eq_size!(PrimeBagFor<usize, 32>, u32);
+        assert_eq_size!(PrimeBagFor<usize, 64>, u64);
+        assert_eq_size!(PrimeBagFor<usize, 128>, u128);
+    }
+
+    #[test]
+    fn test_iter_groups_with_rest() {
+        let bag = PrimeBag64::<usize>::try_from_iter([0, 0, 1, 3, 3, 3]).unwrap();
+        let v: Vec<_> = bag.iter_groups_with_rest().collect();
 
         assert_eq!(
             v,
             [
-                (1, NonZeroUsize::new(3).unwrap()),
-                (3, NonZeroUsize::new(2).unwrap()),
-                (4, NonZeroUsize::new(3).unwrap())
+                (
+                    (0, NonZeroUsize::new(2).unwrap()),
+                    PrimeBag64::try_from_iter([1, 3, 3, 3]).unwrap()
+                ),
+                (
+                    (1, NonZeroUsize::new(1).unwrap()),
+                    PrimeBag64::try_from_iter([3, 3, 3]).unwrap()
+                ),
+                ((3, NonZeroUsize::new(3).unwrap()), PrimeBag64::EMPTY),
             ]
         );
+
+        let mut previous = bag;
+        for ((element, count), rest) in bag.iter_groups_with_rest() {
+            assert!(rest.is_subset(&previous));
+            let removed = previous.try_difference(&rest).unwrap();
+            let expected_removed = PrimeBag64::EMPTY
+                .try_insert_many(element, u32::try_from(count.get()).unwrap())
+                .unwrap();
+            assert_eq!(removed, expected_removed);
+            previous = rest;
+        }
     }
 
     #[test]
@@ -664,6 +2666,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_widen() {
+        let expected = PrimeBag128::<usize>::try_from_iter([1, 2, 3]).unwrap();
+
+        assert_eq!(
+            PrimeBag8::<usize>::try_from_iter([1, 2, 3])
+                .unwrap()
+                .widen(),
+            expected
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_iter([1, 2, 3])
+                .unwrap()
+                .widen(),
+            expected
+        );
+        assert_eq!(
+            PrimeBag32::<usize>::try_from_iter([1, 2, 3])
+                .unwrap()
+                .widen(),
+            expected
+        );
+        assert_eq!(
+            PrimeBag64::<usize>::try_from_iter([1, 2, 3])
+                .unwrap()
+                .widen(),
+            expected
+        );
+        assert_eq!(expected.widen(), expected);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let b8 = PrimeBag8::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        let b16 = PrimeBag16::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        let b32 = PrimeBag32::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        let b64 = PrimeBag64::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        let b128 = PrimeBag128::<usize>::try_from_iter([1, 2, 3]).unwrap();
+
+        assert_eq!(b8.content_hash(), b16.content_hash());
+        assert_eq!(b8.content_hash(), b32.content_hash());
+        assert_eq!(b8.content_hash(), b64.content_hash());
+        assert_eq!(b8.content_hash(), b128.content_hash());
+
+        let other = PrimeBag128::<usize>::try_from_iter([1, 2, 4]).unwrap();
+        assert_ne!(b128.content_hash(), other.content_hash());
+        assert_ne!(PrimeBag8::<usize>::EMPTY.content_hash(), b8.content_hash());
+    }
+
     #[test]
     fn test_try_extend() {
         let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap();
@@ -679,6 +2730,62 @@ mod tests {
         assert_eq!(elements, [1, 2, 2, 3, 3, 3]);
     }
 
+    #[test]
+    fn test_try_from_sorted_indices() {
+        let expected = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(
+            PrimeBag16::try_from_sorted_indices(&[1, 2, 2, 3, 3, 3]),
+            Some(expected)
+        );
+        assert_eq!(
+            PrimeBag16::try_from_sorted_indices(&[3, 2, 1, 3, 2, 3]),
+            Some(expected)
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_sorted_indices(&[]),
+            Some(PrimeBag16::EMPTY)
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_sorted_indices(&[1, 2, 2, 3, 3, 3, 4]),
+            None
+        ); //The bag created would be too big
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_sorted_indices(&[1, 1000]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_try_extend_counts() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1]).unwrap();
+        let expected = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.try_extend_counts([(2, 2), (3, 3)]), Some(expected));
+        assert_eq!(
+            bag.try_extend_counts([(2, 2), (4, 0), (3, 3)]),
+            Some(expected)
+        );
+        assert_eq!(bag.try_extend_counts([(1000, 0)]), Some(bag));
+        assert_eq!(bag.try_extend_counts([(1000, 1)]), None);
+        assert_eq!(bag.try_extend_counts([(0, 16)]), None); //The bag created would be too big
+        assert_eq!(bag.try_extend_counts([(0, u32::MAX)]), None);
+    }
+
+    #[test]
+    fn test_flat_map_to_bag() {
+        let sub_bags = [
+            PrimeBag32::<usize>::try_from_iter([0, 1]).unwrap(),
+            PrimeBag32::<usize>::try_from_iter([1, 2, 2]).unwrap(),
+            PrimeBag32::<usize>::EMPTY,
+        ];
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 1, 1, 2]).unwrap();
+
+        let expected = PrimeBag32::<usize>::try_from_iter([0, 1, 1, 2, 2, 1, 2, 2]).unwrap();
+        assert_eq!(bag.flat_map_to_bag(|x| sub_bags[x]), Some(expected));
+
+        let big_bag = PrimeBag32::<usize>::try_from_iter([1; 10]).unwrap();
+        assert_eq!(big_bag.flat_map_to_bag(|x| sub_bags[x]), None); //The bag created would be too big
+    }
+
     #[test]
     fn test_count_instances() {
         let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
@@ -695,6 +2802,23 @@ mod tests {
         assert_eq!(bag.count_instances(0), 3);
     }
 
+    #[test]
+    fn test_count_instances_max() {
+        let bag = PrimeBag128::<usize>::EMPTY.try_insert_many(0, 127).unwrap();
+        assert_eq!(bag.try_insert(0), None);
+        assert_eq!(bag.count_instances(0), 127);
+    }
+
+    #[test]
+    fn test_count_instances_nonzero() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.count_instances_nonzero(0), NonZeroUsize::new(1));
+        assert_eq!(bag.count_instances_nonzero(1), None);
+        assert_eq!(bag.count_instances_nonzero(2), NonZeroUsize::new(2));
+        assert_eq!(bag.count_instances_nonzero(3), NonZeroUsize::new(3));
+        assert_eq!(bag.count_instances_nonzero(1000), None);
+    }
+
     #[test]
     fn test_contains() {
         let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
@@ -709,6 +2833,71 @@ mod tests {
         assert!(bag.contains_at_least(2, 2));
         assert!(!bag.contains_at_least(2, 3));
         assert!(!bag.contains_at_least(1000, 1)); // it is impossible for the bag to contain this value
+        assert!(bag.contains_at_least(4, 0));
+    }
+
+    #[test]
+    fn test_is_exactly() {
+        let bag = PrimeBag16::<usize>::try_from_iter([2]).unwrap();
+        assert!(bag.is_exactly(2));
+        assert!(!bag.is_exactly(0));
+        assert!(!bag.is_exactly(1000));
+
+        for multiple in [vec![2, 2], vec![0, 2], vec![2, 3]] {
+            let bag = PrimeBag16::<usize>::try_from_iter(multiple).unwrap();
+            assert!(!bag.is_exactly(2));
+        }
+        assert!(!PrimeBag16::<usize>::EMPTY.is_exactly(0));
+    }
+
+    #[test]
+    fn test_contains_at_least_large_n() {
+        let bag = PrimeBag8::<usize>::try_from_iter([0; 7]).unwrap();
+        assert!(bag.contains_at_least(0, 7));
+        assert!(!bag.contains_at_least(0, 8));
+        assert!(!bag.contains_at_least(0, u32::MAX));
+
+        let bag = PrimeBag8::<usize>::try_from_iter([1; 5]).unwrap();
+        assert!(bag.contains_at_least(1, 5));
+        assert!(!bag.contains_at_least(1, 6)); // 3^6 overflows a u8
+        assert!(!bag.contains_at_least(1, u32::MAX));
+
+        let bag = PrimeBag16::<usize>::try_from_iter([2; 6]).unwrap();
+        assert!(bag.contains_at_least(2, 6));
+        assert!(!bag.contains_at_least(2, 7)); // 5^7 overflows a u16
+        assert!(!bag.contains_at_least(3, 100));
+        assert!(!PrimeBag16::<usize>::EMPTY.contains_at_least(3, 100));
+        assert!(PrimeBag16::<usize>::EMPTY.contains_at_least(3, 0));
+    }
+
+    #[test]
+    pub fn test_index_of_prime_u64() {
+        for index in 0..NUM_PRIME_INDICES {
+            let prime = Helpers128::get_prime(index).unwrap().get();
+            assert_eq!(
+                index_of_prime_u64(u64::try_from(prime).unwrap()),
+                Some(index)
+            );
+        }
+        for not_prime in [0, 1, 4, 9, 15, 25, 121, u64::MAX] {
+            assert_eq!(index_of_prime_u64(not_prime), None);
+        }
+        let after_last = Helpers128::get_prime(NUM_PRIME_INDICES - 1).unwrap().get() + 2;
+        assert_eq!(index_of_prime_u64(u64::try_from(after_last).unwrap()), None);
+    }
+
+    #[test]
+    pub fn test_debug() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 1, 2]).unwrap();
+        assert_eq!(format!("{bag:?}"), "PrimeBag16(45)");
+        assert_eq!(
+            format!("{:?}", bag.debug_contents()),
+            "PrimeBag16(45 = {1: 2, 2: 1})"
+        );
+        assert_eq!(
+            format!("{:?}", PrimeBag8::<usize>::EMPTY.debug_contents()),
+            "PrimeBag8(1 = {})"
+        );
     }
 
     #[test]
@@ -720,6 +2909,106 @@ mod tests {
         assert_eq!(bag.try_insert(4), None);
     }
 
+    #[test]
+    pub fn test_try_insert_returning_prev() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3, 0]).unwrap();
+        assert_eq!(bag.try_insert_returning_prev(0), Some((expected_bag, 0)));
+
+        let mut bag = PrimeBag16::<usize>::EMPTY;
+        for expected_prev in 0..15 {
+            let (new_bag, prev) = bag.try_insert_returning_prev(0).unwrap();
+            assert_eq!(prev, expected_prev);
+            bag = new_bag;
+        }
+        assert_eq!(bag.try_insert_returning_prev(0), None);
+        assert_eq!(bag.try_insert_returning_prev(1000), None);
+    }
+
+    #[test]
+    pub fn test_result_variants() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        let sub_bag = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
+
+        assert_eq!(bag.try_insert_result(0), Ok(bag.try_insert(0).unwrap()));
+        assert_eq!(
+            bag.try_insert_result(4),
+            Err(PrimeBagError::CapacityExceeded)
+        );
+        assert_eq!(
+            bag.try_insert_result(1000),
+            Err(PrimeBagError::IndexOutOfRange)
+        );
+
+        assert_eq!(
+            PrimeBag16::<usize>::from_iter_checked([1, 2, 2, 3, 3, 3]),
+            Ok(bag)
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::from_iter_checked([1, 2, 2, 3, 3, 3, 4]),
+            Err(PrimeBagError::CapacityExceeded)
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::from_iter_checked([1, 1000]),
+            Err(PrimeBagError::IndexOutOfRange)
+        );
+
+        assert_eq!(
+            sub_bag.try_sum_result(&sub_bag),
+            Ok(sub_bag.try_sum(&sub_bag).unwrap())
+        );
+        assert_eq!(
+            bag.try_sum_result(&bag),
+            Err(PrimeBagError::CapacityExceeded)
+        );
+
+        assert_eq!(
+            bag.try_difference_result(&sub_bag),
+            Ok(bag.try_difference(&sub_bag).unwrap())
+        );
+        assert_eq!(
+            sub_bag.try_difference_result(&bag),
+            Err(PrimeBagError::NotASubset)
+        );
+    }
+
+    #[test]
+    pub fn test_insert_as_many_as_fit() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap();
+        //Note: the original bag has space to add 3 copies of 3 but not 4 copies
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.insert_as_many_as_fit(3, 10), (expected_bag, 3));
+        assert_eq!(bag.insert_as_many_as_fit(3, 3), (expected_bag, 3));
+
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3]).unwrap();
+        assert_eq!(bag.insert_as_many_as_fit(3, 2), (expected_bag, 2));
+
+        assert_eq!(bag.insert_as_many_as_fit(3, 0), (bag, 0));
+        assert_eq!(bag.insert_as_many_as_fit(1000, 5), (bag, 0));
+        assert_eq!(expected_bag.insert_as_many_as_fit(20, 5), (expected_bag, 0));
+    }
+
+    #[test]
+    pub fn test_fill_with() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap();
+        //Note: the original bag has space to add 3 copies of 3 but not 4 copies
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.fill_with(3), (expected_bag, 3));
+        assert_eq!(expected_bag.fill_with(3), (expected_bag, 0));
+        assert_eq!(bag.fill_with(1000), (bag, 0));
+
+        let (full, count) = PrimeBag16::<usize>::EMPTY.fill_with(0);
+        assert_eq!(count, 15);
+        assert_eq!(full.count_instances(0), 15);
+
+        for value in 0..32 {
+            let (filled, count) = bag.fill_with(value);
+            assert_eq!((filled, count), bag.insert_as_many_as_fit(value, u32::MAX));
+            assert!(filled.try_insert(value).is_none());
+        }
+    }
+
     #[test]
     pub fn test_try_remove() {
         let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap();
@@ -738,6 +3027,27 @@ mod tests {
         assert_eq!(bag.try_insert_many(3, 4), None);
     }
 
+    #[test]
+    pub fn test_try_apply_deltas() {
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 0, 1, 2, 2, 2]).unwrap();
+
+        let expected = PrimeBag32::<usize>::try_from_iter([0, 1, 1, 1, 2, 3]).unwrap();
+        assert_eq!(
+            bag.try_apply_deltas(&[(0, -1), (1, 2), (2, -2), (3, 1)]),
+            Some(expected)
+        );
+        let expected = PrimeBag32::<usize>::try_from_iter([1]).unwrap();
+        assert_eq!(bag.try_apply_deltas(&[(2, -3), (0, -2)]), Some(expected));
+        assert_eq!(bag.try_apply_deltas(&[]), Some(bag));
+        assert_eq!(bag.try_apply_deltas(&[(4, 0), (0, 2), (0, -2)]), Some(bag));
+
+        assert_eq!(bag.try_apply_deltas(&[(1, -2)]), None); //There is only one copy of 1
+        assert_eq!(bag.try_apply_deltas(&[(3, 1), (3, -1)]), None); //Removals are applied first
+        assert_eq!(bag.try_apply_deltas(&[(0, -1), (1000, -1)]), None); //The index is out of range
+        assert_eq!(bag.try_apply_deltas(&[(0, 1), (9, 5)]), None); //The bag created would be too big
+        assert_eq!(bag.try_apply_deltas(&[(0, i32::MIN)]), None);
+    }
+
     #[test]
     pub fn test_is_superset() {
         let super_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
@@ -745,6 +3055,40 @@ mod tests {
         assert!(super_bag.is_superset(&sub_bag));
         assert!(super_bag.is_superset(&super_bag));
         assert!(!sub_bag.is_superset(&super_bag));
+        assert!(super_bag.is_superset(&PrimeBag16::EMPTY));
+        assert!(!PrimeBag16::EMPTY.is_superset(&sub_bag));
+    }
+
+    #[test]
+    pub fn test_is_superset_random() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        for _ in 0..1000 {
+            let left = PrimeBag64::<usize>::from_inner(rng.gen());
+            let right = PrimeBag64::<usize>::from_inner(rng.gen());
+            for (a, b) in [(left, right), (right, left), (left, left)] {
+                let expected = a.into_inner_primitive() % b.into_inner_primitive() == 0;
+                assert_eq!(a.is_superset(&b), expected);
+                assert_eq!(b.is_subset(&a), expected);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_count_supersets() {
+        let word = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        let racks = [
+            PrimeBag16::<usize>::try_from_iter([1, 2, 3]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 3, 3]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 1, 2, 2]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([2]).unwrap(),
+            PrimeBag16::<usize>::EMPTY,
+        ];
+
+        assert_eq!(word.count_supersets(&racks), 2);
+        assert_eq!(PrimeBag16::<usize>::EMPTY.count_supersets(&racks), 5);
+        assert_eq!(word.count_supersets(&[]), 0);
     }
 
     #[test]
@@ -763,6 +3107,19 @@ mod tests {
         assert!(PrimeBag16::<usize>::default().is_empty());
     }
 
+    #[test]
+    pub fn test_is_empty_equivalence() {
+        for_each_width!(Bag => {
+            for bag in random_fillings::<Bag<usize>>(32, false).into_iter().flatten() {
+                let is_empty = bag.is_empty();
+                assert_eq!(is_empty, bag.count() == 0);
+                assert_eq!(is_empty, bag.dedup().count() == 0);
+                assert_eq!(is_empty, bag.into_iter().next().is_none());
+                assert_eq!(is_empty, bag.iter_groups().next().is_none());
+            }
+        });
+    }
+
     #[test]
     pub fn test_try_union() {
         let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
@@ -773,33 +3130,425 @@ mod tests {
 
         let friend = PrimeBag16::<usize>::try_from_iter([5]).unwrap();
 
-        assert_eq!(expected_bag.try_union(&friend), None); //The bag created would be too big
+        assert_eq!(expected_bag.try_union(&friend), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_try_elementwise_max_owned_chaining() {
+        fn chain(
+            bag: PrimeBag16<usize>,
+            a: PrimeBag16<usize>,
+            b: PrimeBag16<usize>,
+            c: PrimeBag16<usize>,
+        ) -> Option<PrimeBag16<usize>> {
+            bag.try_elementwise_max_owned(a)?
+                .try_elementwise_max_owned(b)?
+                .try_elementwise_max_owned(c)
+        }
+
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        let a = PrimeBag16::<usize>::try_from_iter([2, 2]).unwrap();
+        let b = PrimeBag16::<usize>::try_from_iter([1, 3]).unwrap();
+        let c = PrimeBag16::<usize>::try_from_iter([3]).unwrap();
+
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3]).unwrap();
+        assert_eq!(chain(bag, a, b, c), Some(expected_bag));
+        assert_eq!(bag.try_elementwise_max_owned(a), bag.try_union(&a));
+
+        let friend = PrimeBag16::<usize>::try_from_iter([5, 5]).unwrap();
+        assert_eq!(chain(bag, a, b, friend), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_approx_bits() {
+        assert_eq!(PrimeBag16::<usize>::EMPTY.approx_bits(), 1);
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_iter([0])
+                .unwrap()
+                .approx_bits(),
+            2
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_iter([1])
+                .unwrap()
+                .approx_bits(),
+            2
+        );
+
+        let (full, _) = PrimeBag16::<usize>::EMPTY.fill_with(0);
+        assert_eq!(full.approx_bits(), 16);
+        let (full, _) = PrimeBag128::<usize>::EMPTY.fill_with(1);
+        assert_eq!(full.approx_bits(), 127);
+
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        for other in [0, 1, 2, 3, 4, 5] {
+            let other = PrimeBag16::<usize>::try_from_iter([other, other]).unwrap();
+            let bits = bag.approx_bits() + other.approx_bits();
+            if bits <= 16 {
+                assert!(bag.fits_sum(&other));
+            } else if bits > 17 {
+                assert!(!bag.fits_sum(&other));
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_try_union_with_iter() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+
+        for elements in [vec![], vec![2, 3, 4], vec![1, 1, 1], vec![5], vec![4, 4]] {
+            let expected = PrimeBag16::<usize>::try_from_iter(elements.clone())
+                .and_then(|bag2| bag.try_union(&bag2));
+            assert_eq!(bag.try_union_with_iter(elements), expected);
+        }
+
+        assert_eq!(bag.try_union_with_iter([1000]), None); //The element is out of range
+    }
+
+    #[test]
+    pub fn test_try_sum() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.try_sum(&bag2), Some(expected_bag));
+        assert_eq!(expected_bag.try_sum(&expected_bag), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_try_sum_diagnostic() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 1]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 2, 5, 5, 5]).unwrap();
+
+        assert_eq!(
+            bag1.try_sum_diagnostic(&bag1),
+            Ok(bag1.try_sum(&bag1).unwrap())
+        );
+        assert_eq!(bag1.try_sum_diagnostic(&PrimeBag16::EMPTY), Ok(bag1));
+        assert_eq!(bag1.try_sum(&bag2), None);
+        assert_eq!(bag1.try_sum_diagnostic(&bag2), Err(5)); // 12 * 2 * 5 * 13 * 13 fits but 12 * 2 * 5 * 13 * 13 * 13 does not
+        assert_eq!(bag2.try_sum_diagnostic(&bag1), Err(0)); // 2 * 5 * 13 * 13 * 13 * 2 fits but 2 * 5 * 13 * 13 * 13 * 2 * 2 does not
+
+        let unsupported = PrimeBag16::<usize>::from_inner(NonZeroU16::new(65521).unwrap()); // 65521 is prime but has no prime index
+        assert_eq!(
+            bag1.try_sum_diagnostic(&unsupported),
+            Err(helpers::Helpers16::NUM_PRIMES)
+        );
+    }
+
+    #[test]
+    pub fn test_scale() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 1]).unwrap(); // 12
+
+        let expected = PrimeBag16::<usize>::try_from_iter([0, 0, 0, 0, 1, 1]).unwrap();
+        assert_eq!(bag.try_scale(2), Some(expected));
+        assert_eq!(bag.try_scale(1), Some(bag));
+        assert_eq!(bag.try_scale(0), Some(PrimeBag16::EMPTY));
+        assert_eq!(bag.try_scale(5), None); //The bag created would be too big
+
+        // 12^4 = 20736 fits in a u16 but 12^5 does not
+        let expected =
+            PrimeBag16::<usize>::try_from_iter([0; 8].into_iter().chain([1; 4])).unwrap();
+        assert_eq!(bag.saturating_scale(4), expected);
+        assert_eq!(bag.saturating_scale(5), expected);
+        assert_eq!(bag.saturating_scale(u32::MAX), expected);
+        assert_eq!(bag.saturating_scale(2), bag.try_scale(2).unwrap());
+        assert_eq!(bag.saturating_scale(1), bag);
+        assert_eq!(bag.saturating_scale(0), PrimeBag16::EMPTY);
+        assert_eq!(
+            PrimeBag16::<usize>::EMPTY.saturating_scale(7),
+            PrimeBag16::EMPTY
+        );
+
+        let full = PrimeBag16::<usize>::try_from_iter([0; 15]).unwrap();
+        assert_eq!(full.saturating_scale(3), full);
+
+        assert_eq!(bag.max_scale(), 4);
+        assert_eq!(full.max_scale(), 1);
+        assert_eq!(PrimeBag16::<usize>::EMPTY.max_scale(), u32::MAX);
+        assert_eq!(
+            PrimeBag128::<usize>::try_from_iter([0])
+                .unwrap()
+                .max_scale(),
+            127
+        );
+        for bag in [bag, full] {
+            assert!(bag.try_scale(bag.max_scale()).is_some());
+            assert!(bag.try_scale(bag.max_scale() + 1).is_none());
+        }
+    }
+
+    #[test]
+    pub fn test_product() {
+        let bags = [
+            PrimeBag16::<usize>::try_from_iter([0, 1]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([0]).unwrap(),
+        ];
+
+        let expected = PrimeBag16::<usize>::try_from_iter([0, 0, 1, 1, 2]).unwrap();
+        assert_eq!(bags.iter().product::<PrimeBag16<usize>>(), expected);
+        assert_eq!(bags.into_iter().product::<PrimeBag16<usize>>(), expected);
+        assert_eq!(
+            core::iter::empty::<PrimeBag16<usize>>().product::<PrimeBag16<usize>>(),
+            PrimeBag16::EMPTY
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The resulting bag would be too large")]
+    pub fn test_product_overflow() {
+        let bag = PrimeBag16::<usize>::try_from_iter([9]).unwrap();
+        let _ = [bag; 4].into_iter().product::<PrimeBag16<usize>>();
+    }
+
+    #[test]
+    pub fn test_overflowing_sum() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(bag.overflowing_sum(&bag2), (expected_bag, false));
+        assert!(expected_bag.overflowing_sum(&expected_bag).1); //The bag created would be too big
+
+        let zeros = PrimeBag8::<usize>::try_from_iter([0, 0, 0, 0]).unwrap();
+        assert!(zeros.overflowing_sum(&zeros).1); //The wrapped product would be zero
+    }
+
+    #[test]
+    pub fn test_intersection_floor() {
+        let bag1 = PrimeBag64::<usize>::try_from_iter([0, 0, 0, 1, 1, 2]).unwrap();
+        let bag2 = PrimeBag64::<usize>::try_from_iter([0, 0, 0, 1, 1, 1, 2]).unwrap();
+
+        for (k, expected) in [
+            (0, vec![]),
+            (1, vec![0, 0, 0, 1, 1, 2]),
+            (2, vec![0, 0, 1, 1]),
+            (3, vec![0, 0, 0]),
+            (4, vec![]),
+        ] {
+            let expected = PrimeBag64::<usize>::try_from_iter(expected).unwrap();
+            assert_eq!(bag1.intersection_floor(&bag2, k), expected);
+        }
+    }
+
+    #[test]
+    pub fn test_elementwise_max_and_min() {
+        for_each_width!(Bag => {
+            let bag = Bag::<usize>::try_from_iter([0, 1, 1]).unwrap();
+            let bag2 = Bag::<usize>::try_from_iter([0, 0, 1]).unwrap();
+
+            let max = Bag::<usize>::try_from_iter([0, 0, 1, 1]).unwrap();
+            let min = Bag::<usize>::try_from_iter([0, 1]).unwrap();
+            let sum = Bag::<usize>::try_from_iter([0, 0, 0, 1, 1, 1]).unwrap();
+
+            assert_eq!(bag.try_elementwise_max(&bag2), Some(max));
+            assert_eq!(bag.try_union(&bag2), Some(max));
+            assert_eq!(bag.elementwise_min(&bag2), min);
+            assert_eq!(bag.intersection(&bag2), min);
+            assert_eq!(bag.try_sum(&bag2), Some(sum));
+        });
+    }
+
+    #[test]
+    pub fn test_try_weighted_union() {
+        let bag1 = PrimeBag32::<usize>::try_from_iter([0, 1, 1, 1, 2]).unwrap();
+        let bag2 = PrimeBag32::<usize>::try_from_iter([1, 2, 2, 3]).unwrap();
+
+        let expected = PrimeBag32::<usize>::try_from_iter([0, 1, 1, 1, 2, 3]).unwrap();
+        assert_eq!(bag1.try_weighted_union(&bag2, true), Some(expected));
+        assert_eq!(bag2.try_weighted_union(&bag1, false), Some(expected));
+
+        let expected = PrimeBag32::<usize>::try_from_iter([0, 1, 2, 2, 3]).unwrap();
+        assert_eq!(bag1.try_weighted_union(&bag2, false), Some(expected));
+        assert_eq!(bag2.try_weighted_union(&bag1, true), Some(expected));
+
+        for prefer_self in [true, false] {
+            assert_eq!(bag1.try_weighted_union(&bag1, prefer_self), Some(bag1));
+            assert_eq!(
+                bag1.try_weighted_union(&PrimeBag32::EMPTY, prefer_self),
+                Some(bag1)
+            );
+            assert_eq!(
+                PrimeBag32::EMPTY.try_weighted_union(&bag1, prefer_self),
+                Some(bag1)
+            );
+        }
+
+        let big = PrimeBag32::<usize>::try_from_iter([9, 9, 9, 9]).unwrap();
+        let other = PrimeBag32::<usize>::try_from_iter([8, 8, 8]).unwrap();
+        assert_eq!(big.try_weighted_union(&other, true), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_fits_sum_and_union() {
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
+        let bag3 = PrimeBag16::<usize>::try_from_iter([2, 3, 4]).unwrap();
+        let friend = PrimeBag16::<usize>::try_from_iter([5]).unwrap();
+
+        for (a, b) in [
+            (bag, bag2),
+            (bag, bag3),
+            (bag, friend),
+            (bag3, friend),
+            (bag, bag),
+        ] {
+            assert_eq!(a.fits_sum(&b), a.try_sum(&b).is_some());
+            assert_eq!(a.fits_union(&b), a.try_union(&b).is_some());
+        }
+
+        assert!(bag.fits_sum(&bag2));
+        assert!(!bag.fits_sum(&bag3));
+        assert!(bag.fits_union(&bag3));
+        assert!(!bag.fits_union(&bag3.try_union(&friend).unwrap()));
+    }
+
+    #[test]
+    pub fn test_intersection() {
+        let bag_1_1_3 = PrimeBag16::<usize>::try_from_iter([1, 1, 3]).unwrap();
+        let bag_1_2 = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1]).unwrap();
+        assert_eq!(bag_1_1_3.intersection(&bag_1_2), expected_bag);
+    }
+
+    #[test]
+    pub fn test_strip_common() {
+        let shared = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        let mut bags = [
+            PrimeBag16::<usize>::try_from_iter([1, 1, 2, 3]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([0, 1, 2]).unwrap(),
+        ];
+        assert_eq!(PrimeBag16::intersection_of(&bags), Some(shared));
+        assert_eq!(PrimeBag16::<usize>::intersection_of(&[]), None);
+
+        assert_eq!(PrimeBag16::strip_common(&mut bags), shared);
+        assert_eq!(
+            bags,
+            [
+                PrimeBag16::<usize>::try_from_iter([1, 3]).unwrap(),
+                PrimeBag16::<usize>::try_from_iter([2]).unwrap(),
+                PrimeBag16::<usize>::try_from_iter([0]).unwrap(),
+            ]
+        );
+        assert_eq!(PrimeBag16::intersection_of(&bags), Some(PrimeBag16::EMPTY));
+        assert_eq!(PrimeBag16::strip_common(&mut bags), PrimeBag16::EMPTY);
+        assert_eq!(
+            PrimeBag16::<usize>::strip_common(&mut []),
+            PrimeBag16::EMPTY
+        );
+    }
+
+    #[test]
+    pub fn test_intersection_of_and_try_union_of() {
+        let bags = [
+            PrimeBag16::<usize>::try_from_iter([1, 1, 2, 3]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 1, 2]).unwrap(),
+        ];
+
+        let expected = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        assert_eq!(PrimeBag16::intersection_of(&bags), Some(expected));
+        assert_eq!(PrimeBag16::intersection_of(&bags[..1]), Some(bags[0]));
+        assert_eq!(PrimeBag16::<usize>::intersection_of(&[]), None);
+
+        let expected = PrimeBag16::<usize>::try_from_iter([1, 1, 2, 2, 3]).unwrap();
+        assert_eq!(PrimeBag16::try_union_of(&bags), Some(expected));
+        assert_eq!(
+            PrimeBag16::<usize>::try_union_of(&[]),
+            Some(PrimeBag16::EMPTY)
+        );
+
+        let too_big = [
+            expected,
+            PrimeBag16::<usize>::try_from_iter([4, 4]).unwrap(),
+        ];
+        assert_eq!(PrimeBag16::try_union_of(&too_big), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_try_union_all_into() {
+        let bags = [
+            PrimeBag16::<usize>::try_from_iter([1, 1, 2, 3]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([1, 2, 2]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([4, 4]).unwrap(),
+            PrimeBag16::<usize>::try_from_iter([5, 6]).unwrap(),
+        ];
+        assert_eq!(PrimeBag16::try_union_of(&bags), None);
+
+        let expected = PrimeBag128::<usize>::try_from_iter([1, 1, 2, 2, 3, 4, 4, 5, 6]).unwrap();
+        assert_eq!(PrimeBag128::try_union_all_into(bags), Some(expected));
+        assert_eq!(
+            PrimeBag16::<usize>::try_union_all_into(bags[..2].iter().copied()),
+            PrimeBag16::try_union_of(&bags[..2])
+        );
+        assert_eq!(
+            PrimeBag128::<usize>::try_union_all_into::<PrimeBag16<usize>, _>([]),
+            Some(PrimeBag128::EMPTY)
+        );
+
+        let too_big = [
+            PrimeBag64::<usize>::try_from_iter([20; 10]).unwrap(),
+            PrimeBag64::<usize>::try_from_iter([21; 10]).unwrap(),
+            PrimeBag64::<usize>::try_from_iter([22; 10]).unwrap(),
+        ];
+        assert_eq!(PrimeBag128::try_union_all_into(too_big), None); //The bag created would be too big
     }
 
     #[test]
-    pub fn test_try_sum() {
-        let bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+    pub fn test_try_difference() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
         let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
-        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
-        assert_eq!(bag.try_sum(&bag2), Some(expected_bag));
-        assert_eq!(expected_bag.try_sum(&expected_bag), None); //The bag created would be too big
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+        assert_eq!(bag1.try_difference(&bag2), Some(expected_bag));
+        assert_eq!(bag2.try_difference(&bag1), None); //bag2 is not a superset of bag1
+        assert_eq!(bag1.try_difference(&PrimeBag16::EMPTY), Some(bag1));
     }
 
     #[test]
-    pub fn test_intersection() {
-        let bag_1_1_3 = PrimeBag16::<usize>::try_from_iter([1, 1, 3]).unwrap();
-        let bag_1_2 = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
-        let expected_bag = PrimeBag16::<usize>::try_from_iter([1]).unwrap();
-        assert_eq!(bag_1_1_3.intersection(&bag_1_2), expected_bag);
+    pub fn test_try_difference_reporting() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
+        assert_eq!(bag1.try_difference_reporting(&bag2), Ok(expected_bag));
+
+        let one_extra = PrimeBag16::<usize>::try_from_iter([2, 3, 4]).unwrap();
+        let expected_missing = PrimeBag16::<usize>::try_from_iter([4]).unwrap();
+        assert_eq!(
+            bag1.try_difference_reporting(&one_extra),
+            Err(expected_missing)
+        );
+
+        let many_short = PrimeBag16::<usize>::try_from_iter([0, 0, 1, 1, 2, 2, 2]).unwrap();
+        let expected_missing = PrimeBag16::<usize>::try_from_iter([0, 0, 1, 2]).unwrap();
+        assert_eq!(
+            bag1.try_difference_reporting(&many_short),
+            Err(expected_missing)
+        );
     }
 
+    #[cfg(feature = "alloc")]
     #[test]
-    pub fn test_try_difference() {
+    pub fn test_difference_detailed() {
         let bag1 = PrimeBag16::<usize>::try_from_iter([1, 2, 2, 3, 3, 3]).unwrap();
         let bag2 = PrimeBag16::<usize>::try_from_iter([2, 3]).unwrap();
         let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3, 3]).unwrap();
-        assert_eq!(bag1.try_difference(&bag2), Some(expected_bag));
-        assert_eq!(bag2.try_difference(&bag1), None); //bag2 is not a superset of bag1
+        assert_eq!(bag1.difference_detailed(&bag2), Ok(expected_bag));
+
+        let too_many = PrimeBag16::<usize>::try_from_iter([0, 0, 1, 1, 2, 2, 2]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([3, 3, 3]).unwrap();
+        assert_eq!(
+            bag1.difference_detailed(&too_many),
+            Err((expected_bag, vec![(0, 2), (1, 1), (2, 1)]))
+        );
+    }
+
+    #[test]
+    pub fn test_complement_in() {
+        let universe = PrimeBag16::<usize>::try_from_iter([1, 1, 2, 2, 3]).unwrap();
+        let bag = PrimeBag16::<usize>::try_from_iter([1, 2]).unwrap();
+        let expected_bag = PrimeBag16::<usize>::try_from_iter([1, 2, 3]).unwrap();
+        assert_eq!(bag.complement_in(&universe), Some(expected_bag));
+        assert_eq!(universe.complement_in(&bag), None); //universe is not a subset of bag
     }
 
     #[test]
@@ -846,6 +3595,59 @@ mod tests {
     }
 
     #[test]
+    pub fn test_iter_reverse_random() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        for_each_width!(Bag => {
+            let mut rng = StdRng::seed_from_u64(123);
+            for filling in random_fillings::<Bag<usize>>(32, true) {
+                for bag in filling {
+                    let forward: Vec<usize> = bag.into_iter().collect();
+                    let mut reversed: Vec<usize> = bag.into_iter().rev().collect();
+                    reversed.reverse();
+                    assert_eq!(forward, reversed);
+
+                    let mut expected: std::collections::VecDeque<usize> = forward.into();
+                    let mut iter = bag.into_iter();
+                    loop {
+                        let (e, a) = if rng.gen_bool(0.5) {
+                            (expected.pop_front(), iter.next())
+                        } else {
+                            (expected.pop_back(), iter.next_back())
+                        };
+                        assert_eq!(e, a);
+                        if e.is_none() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    #[test]
+    pub fn test_iter_reverse_element_zero() {
+        for elements in [
+            vec![0],
+            vec![0, 0, 0],
+            vec![0, 1],
+            vec![0, 0, 5],
+            vec![0, 0, 0, 31],
+            vec![7],
+        ] {
+            let bag = PrimeBag128::<usize>::try_from_iter(elements.clone()).unwrap();
+            let mut actual: Vec<usize> = bag.into_iter().rev().collect();
+            actual.reverse();
+            assert_eq!(elements, actual);
+        }
+
+        let bag = PrimeBag128::<usize>::try_from_iter([0; 127]).unwrap();
+        assert_eq!(bag.into_iter().rev().count(), 127);
+        assert!(bag.into_iter().rev().all(|x| x == 0));
+    }
+
+    #[test]
+    #[allow(clippy::iter_nth)]
     pub fn test_iter_nth() {
         let expected: Vec<usize> = vec![0, 0, 0, 1, 1, 2, 2, 3, 3, 5, 7, 13, 19];
         let bag = PrimeBag128::<usize>::try_from_iter(expected.clone()).unwrap();
@@ -858,6 +3660,97 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_iter_nth_back() {
+        let expected: Vec<usize> = vec![0, 0, 0, 1, 1, 2, 2, 3, 3, 5, 7, 13, 19];
+        let bag = PrimeBag128::<usize>::try_from_iter(expected.clone()).unwrap();
+
+        for n in 0..=expected.len() {
+            let e = expected.iter().rev().nth(n).copied();
+            let mut iter = bag.into_iter();
+            let a = iter.nth_back(n);
+            assert_eq!(e, a);
+
+            let remaining = expected.len().saturating_sub(n + 1);
+            let (min, max) = iter.size_hint();
+            assert!(min <= remaining && max.is_some_and(|max| max >= remaining));
+            assert_eq!(iter.clone().count(), remaining);
+            assert_eq!(iter.collect::<Vec<_>>(), expected[..remaining]);
+        }
+
+        let mut iter = bag.into_iter();
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.nth_back(2), Some(7));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.nth_back(5), Some(1));
+        assert_eq!(iter.collect::<Vec<_>>(), [0, 1]);
+
+        let mut iter = bag.into_iter();
+        assert_eq!(iter.nth(3), Some(1));
+        assert_eq!(iter.nth_back(0), Some(19));
+        assert_eq!(iter.nth_back(6), Some(2));
+        assert_eq!(iter.nth_back(0), Some(1));
+        assert_eq!(iter.nth_back(0), None);
+    }
+
+    #[test]
+    pub fn test_iter_nth_back_random() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, true) {
+                let mut expected: Vec<usize> = bag.into_iter().collect();
+                let skip = rng.gen_range(0..=expected.len());
+
+                let mut iter = bag.into_iter();
+                let _ = iter.nth(skip);
+                expected.drain(..(skip + 1).min(expected.len()));
+
+                while !expected.is_empty() {
+                    let n = rng.gen_range(0..=expected.len());
+                    let expected_element = if n < expected.len() {
+                        let index = expected.len() - 1 - n;
+                        let element = expected[index];
+                        expected.truncate(index);
+                        Some(element)
+                    } else {
+                        expected.clear();
+                        None
+                    };
+
+                    assert_eq!(iter.nth_back(n), expected_element);
+                    assert_eq!(iter.clone().collect::<Vec<_>>(), expected);
+                }
+                assert_eq!(iter.nth_back(0), None);
+            }
+        });
+    }
+
+    #[test]
+    pub fn test_iter_short_circuit() {
+        let expected: Vec<usize> = vec![0, 0, 0, 1, 1, 2, 2, 3, 3, 5, 7, 13, 19];
+        let bag = PrimeBag128::<usize>::try_from_iter(expected.clone()).unwrap();
+
+        for value in [0, 1, 2, 3, 5, 7, 13, 19] {
+            let mut iter = bag.into_iter();
+            let mut visited = 0;
+            assert!(iter.any(|x| {
+                visited += 1;
+                x == value
+            }));
+            let first_position = expected.iter().position(|x| *x == value).unwrap();
+            assert_eq!(visited, first_position + 1);
+
+            let rest: Vec<_> = iter.collect();
+            assert_eq!(rest, expected[first_position + 1..]);
+        }
+
+        let mut iter = bag.into_iter();
+        assert_eq!(iter.find(|x| *x > 2), Some(3));
+        assert_eq!(iter.count(), 5);
+    }
+
     #[test]
     pub fn test_iter_last() {
         let expected: Vec<usize> = vec![0, 0, 0, 1, 1, 2, 2, 3, 3, 5, 7, 13, 19];
@@ -866,6 +3759,107 @@ mod tests {
         assert_eq!(expected.last().copied(), bag.into_iter().last());
     }
 
+    #[test]
+    pub fn test_min_and_max() {
+        const _: () = assert!(PrimeBag8::<usize>::MIN.is_empty());
+        const _: () = assert!(PrimeBag8::<usize>::MAX.into_inner_primitive() == u8::MAX);
+        const _: () = assert!(PrimeBag16::<usize>::MAX.into_inner_primitive() == u16::MAX);
+        const _: () = assert!(PrimeBag32::<usize>::MAX.into_inner_primitive() == u32::MAX);
+        const _: () = assert!(PrimeBag64::<usize>::MAX.into_inner_primitive() == u64::MAX);
+        const _: () = assert!(PrimeBag128::<usize>::MAX.into_inner_primitive() == u128::MAX);
+
+        assert_eq!(PrimeBag16::<usize>::MIN, PrimeBag16::EMPTY);
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        assert!(PrimeBag16::MIN < bag && bag < PrimeBag16::MAX);
+        assert_eq!(PrimeBag16::<usize>::MAX.try_insert(0), None);
+        assert_eq!(
+            PrimeBag8::<usize>::MAX.into_iter().collect::<Vec<_>>(),
+            [1, 2, 6]
+        ); // 255 = 3 * 5 * 17
+    }
+
+    #[test]
+    pub fn test_ord() {
+        let bag_0_0 = PrimeBag16::<usize>::try_from_iter([0, 0]).unwrap(); // 4
+        let bag_1 = PrimeBag16::<usize>::try_from_iter([1]).unwrap(); // 3
+        let bag_0_1 = PrimeBag16::<usize>::try_from_iter([0, 1]).unwrap(); // 6
+        let bag_0_2 = PrimeBag16::<usize>::try_from_iter([0, 2]).unwrap(); // 10
+
+        let mut bags = [bag_0_2, bag_0_1, bag_1, PrimeBag16::EMPTY, bag_0_0];
+        bags.sort();
+
+        assert_eq!(bags, [PrimeBag16::EMPTY, bag_1, bag_0_0, bag_0_1, bag_0_2]);
+
+        let bag_2 = PrimeBag16::<usize>::try_from_iter([2]).unwrap(); // 5
+        #[cfg(not(feature = "total-content-ord"))]
+        assert!(bag_2 > bag_0_0);
+        #[cfg(feature = "total-content-ord")]
+        assert!(bag_2 < bag_0_0);
+
+        let random_bags = random_small_bags::<PrimeBag32<usize>>(50, 4, 5);
+        for (a, b) in random_bags
+            .iter()
+            .flat_map(|a| random_bags.iter().map(move |b| (*a, *b)))
+        {
+            assert_eq!(a.cmp(&b) == core::cmp::Ordering::Equal, a == b);
+            assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+
+            #[cfg(not(feature = "total-content-ord"))]
+            assert_eq!(a.cmp(&b), a.into_inner().cmp(&b.into_inner()));
+            #[cfg(feature = "total-content-ord")]
+            {
+                let expected = a.count().cmp(&b.count()).then_with(|| {
+                    a.into_iter()
+                        .collect::<Vec<_>>()
+                        .cmp(&b.into_iter().collect())
+                });
+                assert_eq!(a.cmp(&b), expected);
+                if a.is_superset(&b) && a != b {
+                    assert!(a > b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_ord_max() {
+        let bag16 = PrimeBag16::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        let bag32 = PrimeBag32::<usize>::try_from_iter([0, 1, 2]).unwrap();
+        let bag64 = PrimeBag64::<usize>::try_from_iter([31, 31]).unwrap();
+        let bag128 = PrimeBag128::<usize>::try_from_iter([0; 100]).unwrap();
+
+        assert!(bag16 < PrimeBag16::MAX);
+        assert!(bag32 < PrimeBag32::MAX);
+        assert!(bag64 < PrimeBag64::MAX);
+        assert!(bag128 < PrimeBag128::MAX);
+        assert_eq!(
+            PrimeBag64::<usize>::MAX.cmp(&PrimeBag64::MAX),
+            core::cmp::Ordering::Equal
+        );
+
+        let invalid_a = PrimeBag32::<usize>::from_inner(NonZeroU32::new(1621 * 2).unwrap());
+        let invalid_b = PrimeBag32::<usize>::from_inner(NonZeroU32::new(1627).unwrap());
+        assert!(invalid_a > bag32 && invalid_b > bag32);
+        assert!(invalid_b < invalid_a && invalid_a < PrimeBag32::MAX);
+
+        let mut set = std::collections::BTreeSet::new();
+        set.insert(PrimeBag32::MAX);
+        set.insert(invalid_a);
+        set.insert(bag32);
+        set.insert(invalid_b);
+        set.insert(PrimeBag32::EMPTY);
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            [
+                PrimeBag32::EMPTY,
+                bag32,
+                invalid_b,
+                invalid_a,
+                PrimeBag32::MAX
+            ]
+        );
+    }
+
     #[test]
     pub fn test_empty() {
         let bag = PrimeBag128::<usize>::EMPTY;
@@ -873,6 +3867,18 @@ mod tests {
         assert!(bag.is_empty());
     }
 
+    #[test]
+    pub fn test_count_consistency() {
+        for_each_width!(Bag => {
+            for bag in random_fillings::<Bag<usize>>(32, false).into_iter().flatten() {
+                let iter_count = bag.into_iter().count();
+                let groups_count: usize = bag.iter_groups().map(|(_, c)| c.get()).sum();
+                assert_eq!(iter_count, groups_count);
+                assert_eq!(iter_count, bag.count());
+            }
+        });
+    }
+
     #[test]
     pub fn test_count_is_at_least() {
         assert!(!PrimeBag64::<usize>::EMPTY.is_count_at_least(1));
@@ -885,6 +3891,407 @@ mod tests {
         assert!(!bag.is_count_at_least(7));
     }
 
+    #[test]
+    pub fn test_split_off_half() {
+        for (input, expected_first, expected_second) in [
+            (vec![0, 0, 1, 2, 2, 2, 3], vec![0, 0, 1], vec![2, 2, 2, 3]),
+            (vec![1, 3, 3, 4], vec![1, 3, 3], vec![4]),
+            (vec![5, 5], vec![5, 5], vec![]),
+            (vec![], vec![], vec![]),
+        ] {
+            let bag = PrimeBag64::<usize>::try_from_iter(input).unwrap();
+            let expected_first = PrimeBag64::<usize>::try_from_iter(expected_first).unwrap();
+            let expected_second = PrimeBag64::<usize>::try_from_iter(expected_second).unwrap();
+
+            let (first, second) = bag.split_off_half();
+            assert_eq!(first, expected_first);
+            assert_eq!(second, expected_second);
+            assert_eq!(first.try_sum(&second), Some(bag));
+        }
+    }
+
+    #[test]
+    pub fn test_find_index() {
+        let bag = PrimeBag64::<usize>::try_from_iter([0, 0, 3, 5, 5, 8]).unwrap();
+
+        assert_eq!(bag.find_index(|_| true), Some(0));
+        assert_eq!(bag.find_index(|x| x > 0), Some(3));
+        assert_eq!(bag.find_index(|x| x > 5), Some(8));
+        assert_eq!(bag.find_index(|x| x == 4), None);
+        assert_eq!(PrimeBag64::<usize>::EMPTY.find_index(|_| true), None);
+
+        let mut checked = vec![];
+        assert_eq!(
+            bag.find_index(|x| {
+                checked.push(x);
+                false
+            }),
+            None
+        );
+        assert_eq!(checked, [0, 3, 5, 8]);
+    }
+
+    #[test]
+    pub fn test_combined_counts() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 0, 1, 2, 2]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 3, 3]).unwrap();
+
+        assert_eq!(bag1.count_distinct(), 3);
+        assert_eq!(bag2.count_distinct(), 2);
+        assert_eq!(PrimeBag16::<usize>::EMPTY.count_distinct(), 0);
+
+        assert_eq!(bag1.combined_distinct_count(&bag2), 6);
+        assert_eq!(bag1.combined_total_count(&bag2), 18);
+        assert_eq!(bag1.combined_total_count(&PrimeBag16::EMPTY), 0);
+    }
+
+    #[test]
+    fn test_max_count_table() {
+        for_each_width!(Bag => {
+            let table = Bag::<usize>::max_count_table();
+            assert_eq!(table.len(), Bag::<usize>::EMPTY.count_all_indices().len());
+            for (index, max) in table.iter().enumerate() {
+                assert_eq!(
+                    Bag::<usize>::EMPTY.fill_with(index),
+                    (
+                        Bag::try_from_iter(core::iter::repeat_n(index, *max as usize)).unwrap(),
+                        *max
+                    )
+                );
+            }
+        });
+    }
+
+    #[test]
+    pub fn test_present_mask() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 2, 5]).unwrap();
+        let mask = bag.present_mask();
+        assert_eq!(mask, 0b10_0101);
+        assert_eq!(PrimeBag16::<usize>::EMPTY.present_mask(), 0);
+
+        for index in 0..40 {
+            assert_eq!(
+                bag.contains_via_mask(index, mask),
+                bag.contains(index),
+                "index {index}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_present_mask_random() {
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let mask = bag.present_mask();
+                for index in 0..40 {
+                    assert_eq!(bag.contains_via_mask(index, mask), bag.contains(index));
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    pub fn test_indices_by_count() {
+        let bag = PrimeBag32::<usize>::try_from_iter([1, 2, 2, 3, 3]).unwrap();
+        let map = bag.indices_by_count();
+        let map: Vec<_> = map.into_iter().map(|(c, v)| (c.get(), v)).collect();
+        assert_eq!(map, [(1, vec![1]), (2, vec![2, 3])]);
+
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 0, 0, 4, 5, 5, 5, 7]).unwrap();
+        let map = bag.indices_by_count();
+        let map: Vec<_> = map.into_iter().map(|(c, v)| (c.get(), v)).collect();
+        assert_eq!(map, [(1, vec![4, 7]), (3, vec![0, 5])]);
+
+        assert!(PrimeBag32::<usize>::EMPTY.indices_by_count().is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    pub fn test_to_index_sorted_vec() {
+        let bag = PrimeBag16::<usize>::try_from_iter([5, 0, 2, 0]).unwrap();
+        assert_eq!(bag.to_index_sorted_vec(), [0, 0, 2, 5]);
+        assert!(PrimeBag16::<usize>::EMPTY.to_index_sorted_vec().is_empty());
+
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let indices = bag.to_index_sorted_vec();
+                assert!(indices.windows(2).all(|w| w[0] <= w[1]), "{indices:?}");
+                assert_eq!(indices.len(), bag.count());
+                assert_eq!(Bag::<usize>::try_from_sorted_indices(&indices), Some(bag));
+            }
+        });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    pub fn test_exponent_vec() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 2, 5]).unwrap();
+        let exponents = bag.to_exponent_vec();
+        assert_eq!(exponents.len(), helpers::Helpers16::NUM_PRIMES);
+        assert_eq!(exponents[..6], [2, 0, 1, 0, 0, 1]);
+        assert_eq!(PrimeBag16::try_from_exponent_slice(&exponents), Some(bag));
+
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_exponent_slice(&[]),
+            Some(PrimeBag16::EMPTY)
+        );
+        assert_eq!(PrimeBag16::<usize>::try_from_exponent_slice(&[16]), None);
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_exponent_slice(&[0; 40]),
+            Some(PrimeBag16::EMPTY)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_exponent_vec_random() {
+        for_each_width!(Bag => {
+            for bag in random_full_bags::<Bag<usize>>(32, false) {
+                let exponents = bag.to_exponent_vec();
+                for (index, exponent) in exponents.iter().enumerate() {
+                    assert_eq!(*exponent as usize, bag.count_instances(index));
+                }
+                assert_eq!(Bag::try_from_exponent_slice(&exponents), Some(bag));
+            }
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    pub fn test_count_map() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 2, 5]).unwrap();
+        let map = bag.to_count_map();
+        assert_eq!(
+            map,
+            std::collections::HashMap::from([(0, 2), (2, 1), (5, 1)])
+        );
+        assert_eq!(PrimeBag16::try_from_count_map(&map), Some(bag));
+
+        assert!(PrimeBag16::<usize>::EMPTY.to_count_map().is_empty());
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_count_map(&std::collections::HashMap::from([(1, 0)])),
+            Some(PrimeBag16::EMPTY)
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_count_map(&std::collections::HashMap::from([(0, 16)])),
+            None
+        );
+        assert_eq!(
+            PrimeBag16::<usize>::try_from_count_map(&std::collections::HashMap::from([(1000, 1)])),
+            None
+        );
+    }
+
+    #[test]
+    pub fn test_intersection_with_empty() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(123);
+
+        macro_rules! test_intersection_with_empty {
+            ($bag_x: ident, $nonzero_ux: ty) => {
+                for _ in 0..100 {
+                    let bag = $bag_x::<usize>::from_inner(rng.gen::<$nonzero_ux>());
+                    let other = $bag_x::<usize>::from_inner(rng.gen::<$nonzero_ux>());
+                    let empty = $bag_x::<usize>::EMPTY;
+
+                    assert_eq!(bag.intersection(&empty), empty);
+                    assert_eq!(empty.intersection(&bag), empty);
+                    assert!(bag.is_disjoint(&empty));
+                    assert!(empty.is_disjoint(&bag));
+
+                    let gcd = <$bag_x<usize>>::from_inner(
+                        <$nonzero_ux>::new(gcd::Gcd::gcd(
+                            bag.into_inner().get(),
+                            other.into_inner().get(),
+                        ))
+                        .unwrap(),
+                    );
+                    assert_eq!(bag.intersection(&other), gcd);
+                    assert_eq!(bag.is_disjoint(&other), gcd.is_empty());
+                }
+            };
+        }
+
+        test_intersection_with_empty!(PrimeBag8, NonZeroU8);
+        test_intersection_with_empty!(PrimeBag16, NonZeroU16);
+        test_intersection_with_empty!(PrimeBag32, NonZeroU32);
+        test_intersection_with_empty!(PrimeBag64, NonZeroU64);
+        test_intersection_with_empty!(PrimeBag128, NonZeroU128);
+    }
+
+    #[test]
+    pub fn test_try_union_over_intersection() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 1]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 2, 2]).unwrap();
+        let expected = PrimeBag16::<usize>::try_from_iter([0, 0]).unwrap();
+        assert_eq!(bag1.try_union_over_intersection(&bag2), Some(expected));
+        assert_eq!(bag2.try_union_over_intersection(&bag1), Some(expected));
+
+        let bag1 = PrimeBag64::<usize>::try_from_iter([0, 1, 1, 1, 2, 3]).unwrap();
+        let bag2 = PrimeBag64::<usize>::try_from_iter([0, 0, 1, 2, 2, 4]).unwrap();
+        let expected = PrimeBag64::<usize>::try_from_iter([0, 0, 1, 1, 1, 2, 2]).unwrap();
+        assert_eq!(bag1.try_union_over_intersection(&bag2), Some(expected));
+        assert_eq!(bag1.try_union_over_intersection(&bag1), Some(bag1));
+        assert_eq!(
+            bag1.try_union_over_intersection(&PrimeBag64::EMPTY),
+            Some(PrimeBag64::EMPTY)
+        );
+
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0; 13].into_iter().chain([1])).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0].into_iter().chain([1; 9])).unwrap();
+        assert_eq!(bag1.try_union_over_intersection(&bag2), None); //The bag created would be too big
+    }
+
+    #[test]
+    pub fn test_count_all_indices() {
+        let bag = PrimeBag16::<usize>::try_from_iter([0, 0, 2, 5]).unwrap();
+        let counts = bag.count_all_indices();
+        assert_eq!(counts[..6], [2, 0, 1, 0, 0, 1]);
+        assert_eq!(counts.iter().sum::<usize>(), 4);
+        #[cfg(feature = "alloc")]
+        assert_eq!(bag.count_instances_many(&[5, 0, 1, 1000]), vec![1, 2, 0, 0]);
+        assert!(PrimeBag16::<usize>::EMPTY
+            .count_all_indices()
+            .iter()
+            .all(|c| *c == 0));
+    }
+
+    #[test]
+    pub fn test_minus_count() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 0, 1, 2, 2]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 2, 2, 2, 3]).unwrap();
+
+        assert_eq!(bag1.minus_count(&bag2), 3);
+        assert_eq!(bag2.minus_count(&bag1), 2);
+        assert_eq!(bag1.minus_count(&bag1), 0);
+        assert_eq!(bag1.minus_count(&PrimeBag16::EMPTY), 6);
+        assert_eq!(PrimeBag16::EMPTY.minus_count(&bag1), 0);
+    }
+
+    #[test]
+    pub fn test_symmetric_difference_len() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 0, 1, 2, 2]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 2, 2, 2, 3]).unwrap();
+
+        assert_eq!(bag1.symmetric_difference_len(&bag2), 5);
+        assert_eq!(bag2.symmetric_difference_len(&bag1), 5);
+        assert_eq!(bag1.symmetric_difference_len(&bag1), 0);
+        assert_eq!(bag1.symmetric_difference_len(&PrimeBag16::EMPTY), 6);
+    }
+
+    #[test]
+    pub fn test_symmetric_difference_len_is_metric() {
+        let bag1 = PrimeBag16::<usize>::try_from_iter([0, 0, 1]).unwrap();
+        let bag2 = PrimeBag16::<usize>::try_from_iter([0, 1, 1]).unwrap();
+        let bag3 = PrimeBag16::<usize>::try_from_iter([2]).unwrap();
+
+        assert_eq!(bag1.symmetric_difference_len(&bag2), 2);
+        assert_eq!(bag2.symmetric_difference_len(&bag3), 4);
+        assert_eq!(bag1.symmetric_difference_len(&bag3), 4);
+        assert_eq!(bag1.symmetric_difference_len(&PrimeBag16::EMPTY), 3);
+
+        let random_bags = random_small_bags::<PrimeBag32<usize>>(30, 5, 4);
+
+        for a in &random_bags {
+            assert_eq!(a.symmetric_difference_len(a), 0);
+            for b in &random_bags {
+                assert_eq!(a.symmetric_difference_len(b), b.symmetric_difference_len(a));
+                assert_eq!(a.symmetric_difference_len(b) == 0, a == b);
+                assert_eq!(
+                    a.symmetric_difference_len(b),
+                    a.minus_count(b) + b.minus_count(a)
+                );
+                for c in &random_bags {
+                    assert!(
+                        a.symmetric_difference_len(c)
+                            <= a.symmetric_difference_len(b) + b.symmetric_difference_len(c)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_try_remap() {
+        let bag = PrimeBag32::<usize>::try_from_iter([0, 0, 1, 2, 2, 2]).unwrap();
+
+        let swapped = bag.try_remap(|x| match x {
+            0 => 2,
+            2 => 0,
+            x => x,
+        });
+        let expected = PrimeBag32::<usize>::try_from_iter([2, 2, 1, 0, 0, 0]).unwrap();
+        assert_eq!(swapped, Some(expected));
+
+        let merged = bag.try_remap(|x| x / 2);
+        let expected = PrimeBag32::<usize>::try_from_iter([0, 0, 0, 1, 1, 1]).unwrap();
+        assert_eq!(merged, Some(expected));
+
+        assert_eq!(bag.try_remap(|x| x + 1000), None); //The index is out of range
+        assert_eq!(bag.try_remap(|x| x + 20), None); //The bag created would be too big
+        assert_eq!(
+            PrimeBag32::<usize>::EMPTY.try_remap(|_| 1000),
+            Some(PrimeBag32::EMPTY)
+        );
+    }
+
+    #[test]
+    pub fn test_filter_indices() {
+        let bag = PrimeBag64::<usize>::try_from_iter([0, 0, 0, 1, 2, 2, 3, 4, 4]).unwrap();
+
+        let evens = bag.filter_indices(|index, _| index % 2 == 0);
+        let expected = PrimeBag64::<usize>::try_from_iter([0, 0, 0, 2, 2, 4, 4]).unwrap();
+        assert_eq!(evens, expected);
+
+        let multiples = bag.filter_indices(|_, count| count.get() >= 2);
+        let expected = PrimeBag64::<usize>::try_from_iter([0, 0, 0, 2, 2, 4, 4]).unwrap();
+        assert_eq!(multiples, expected);
+
+        let triples = bag.filter_indices(|_, count| count.get() >= 3);
+        let expected = PrimeBag64::<usize>::try_from_iter([0, 0, 0]).unwrap();
+        assert_eq!(triples, expected);
+
+        assert_eq!(bag.filter_indices(|_, _| true), bag);
+        assert_eq!(bag.filter_indices(|_, _| false), PrimeBag64::EMPTY);
+    }
+
+    #[test]
+    pub fn test_weighted_total() {
+        let bag = PrimeBag64::<usize>::try_from_iter([0, 0, 1, 3, 3, 3]).unwrap();
+
+        assert_eq!(bag.weighted_total(|_| 1), 6);
+        assert_eq!(
+            bag.weighted_total(|i| [1, 3, 3, 2][i]),
+            1 + 1 + 3 + 2 + 2 + 2
+        );
+        assert_eq!(bag.weighted_total(|i| if i == 1 { -10 } else { 1 }), -5);
+        assert_eq!(bag.weighted_total(|_| i64::MAX), i64::MAX);
+        assert_eq!(PrimeBag64::<usize>::EMPTY.weighted_total(|_| 1), 0);
+    }
+
+    #[test]
+    pub fn test_is_single_kind() {
+        for (input, expected) in [
+            (vec![], false),
+            (vec![0], true),
+            (vec![2, 2, 2], true),
+            (vec![0, 0, 0, 0, 0, 0, 0], true),
+            (vec![0, 1], false),
+            (vec![3, 3, 5], false),
+            (vec![0, 0, 9], false),
+        ] {
+            let bag = PrimeBag32::<usize>::try_from_iter(input.iter().copied()).unwrap();
+            assert_eq!(bag.is_single_kind(), expected, "{input:?}");
+        }
+
+        for_each_width!(Bag => {
+            for bag in random_small_bags::<Bag<usize>>(100, 5, 4) {
+                assert_eq!(bag.is_single_kind(), bag.count_distinct() == 1);
+            }
+        });
+    }
+
     #[test]
     pub fn test_dedup() {
         for (input, expected) in [
@@ -939,4 +4346,151 @@ mod tests {
         assert!(is_trait!(PrimeBag128<MyElement>, Ord));
         assert!(is_trait!(PrimeBag128<MyElement>, PartialOrd));
     }
+
+    /// Tests for bags dominated by element 0, which uses faster code paths in several places
+    mod element_zero {
+        use super::*;
+        use std::collections::VecDeque;
+
+        macro_rules! test_element_zero {
+            ($bag_x: ident) => {
+                for extras in [&[][..], &[1], &[3], &[1, 1, 5], &[2, 31]] {
+                    for zeros in 0..128 {
+                        let mut expected: Vec<usize> = vec![0; zeros];
+                        expected.extend_from_slice(extras);
+                        let Some(bag) = $bag_x::<usize>::try_from_iter(expected.iter().copied())
+                        else {
+                            break;
+                        };
+
+                        assert_eq!(bag.contains(0), zeros > 0);
+                        assert_eq!(bag.count_instances(0), zeros);
+                        assert_eq!(bag.count(), expected.len());
+                        for min in 0..=expected.len() + 1 {
+                            assert_eq!(bag.is_count_at_least(min), expected.len() >= min);
+                        }
+
+                        let mut distinct = expected.clone();
+                        distinct.dedup();
+                        assert_eq!(bag.dedup().into_iter().collect::<Vec<_>>(), distinct);
+
+                        let groups: Vec<_> = bag.iter_groups().collect();
+                        if zeros > 0 {
+                            assert_eq!(groups[0], (0, NonZeroUsize::new(zeros).unwrap()));
+                        }
+                        assert_eq!(
+                            groups.iter().map(|(_, c)| c.get()).sum::<usize>(),
+                            expected.len()
+                        );
+
+                        let forward: Vec<_> = bag.into_iter().collect();
+                        assert_eq!(forward, expected);
+                        let backward: Vec<_> = bag.into_iter().rev().collect();
+                        assert_eq!(backward, expected.iter().rev().copied().collect::<Vec<_>>());
+
+                        for n in 0..=expected.len() {
+                            assert_eq!(bag.into_iter().nth(n), expected.get(n).copied());
+
+                            let mut iter = bag.into_iter();
+                            let _ = iter.nth(n);
+                            let rest: Vec<_> = iter.collect();
+                            assert_eq!(rest, expected[(n + 1).min(expected.len())..]);
+                        }
+
+                        let mut iter = bag.into_iter();
+                        let mut deque: VecDeque<usize> = expected.iter().copied().collect();
+                        for step in 0.. {
+                            let (lower, upper) = iter.size_hint();
+                            assert!(lower <= deque.len());
+                            assert!(upper.map_or(true, |upper| upper >= deque.len()));
+                            let (actual, expected) = if step % 2 == 0 {
+                                (iter.next(), deque.pop_front())
+                            } else {
+                                (iter.next_back(), deque.pop_back())
+                            };
+                            assert_eq!(actual, expected);
+                            if actual.is_none() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            };
+        }
+
+        #[test]
+        fn test_count_instances_zero_matches_repeated_division() {
+            use rand::{rngs::StdRng, Rng, SeedableRng};
+            let mut rng = StdRng::seed_from_u64(123);
+
+            macro_rules! test_count_zero {
+                ($bag_x: ident, $nonzero_ux: ty) => {
+                    for _ in 0..1000 {
+                        // Any inner value, including ones with factors outside the prime table
+                        let inner: $nonzero_ux = rng.gen();
+                        let bag = $bag_x::<usize>::from_inner(inner);
+
+                        let mut expected = 0;
+                        let mut value = inner.get();
+                        while value % 2 == 0 {
+                            value /= 2;
+                            expected += 1;
+                        }
+                        assert_eq!(bag.count_instances(0), expected);
+                        assert_eq!(bag.contains(0), expected > 0);
+
+                        // Bags built from elements, which always contain large primes
+                        let mut bag = $bag_x::<usize>::default();
+                        let mut zeros = 0;
+                        loop {
+                            let value = if rng.gen_bool(0.3) {
+                                0
+                            } else {
+                                rng.gen_range(1..32)
+                            };
+                            let Some(new_bag) = bag.try_insert(value) else {
+                                break;
+                            };
+                            bag = new_bag;
+                            if value == 0 {
+                                zeros += 1;
+                            }
+                        }
+                        assert_eq!(bag.count_instances(0), zeros);
+                    }
+                };
+            }
+
+            test_count_zero!(PrimeBag8, NonZeroU8);
+            test_count_zero!(PrimeBag16, NonZeroU16);
+            test_count_zero!(PrimeBag32, NonZeroU32);
+            test_count_zero!(PrimeBag64, NonZeroU64);
+            test_count_zero!(PrimeBag128, NonZeroU128);
+        }
+
+        #[test]
+        fn test_element_zero_8() {
+            test_element_zero!(PrimeBag8);
+        }
+
+        #[test]
+        fn test_element_zero_16() {
+            test_element_zero!(PrimeBag16);
+        }
+
+        #[test]
+        fn test_element_zero_32() {
+            test_element_zero!(PrimeBag32);
+        }
+
+        #[test]
+        fn test_element_zero_64() {
+            test_element_zero!(PrimeBag64);
+        }
+
+        #[test]
+        fn test_element_zero_128() {
+            test_element_zero!(PrimeBag128);
+        }
+    }
 }
@@ -0,0 +1,115 @@
+/// Implements `PrimeBagElement` for a fieldless enum using an explicit table of variants to prime indices.
+/// Each index must be a literal, less than `NUM_PRIME_INDICES`, and unique - this is checked at compile time.
+/// Assign lower indices to more common variants to maximize the possible bag size.
+/// When `from_prime_index` is called with an index not in the table, the first variant listed is returned.
+///
+/// ```rust
+/// use prime_bag::*;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Suit {
+///     Hearts,
+///     Spades,
+///     Clubs,
+/// }
+///
+/// impl_prime_bag_element!(Suit { Spades = 0, Hearts = 1, Clubs = 2 });
+///
+/// assert_eq!(Suit::Hearts.to_prime_index(), 1);
+/// assert_eq!(Suit::from_prime_index(2), Suit::Clubs);
+/// assert_eq!(Suit::from_prime_index(100), Suit::Spades);
+/// ```
+///
+/// Duplicate indices do not compile
+///
+/// ```compile_fail
+/// use prime_bag::*;
+///
+/// enum Suit {
+///     Hearts,
+///     Spades,
+/// }
+///
+/// impl_prime_bag_element!(Suit { Spades = 0, Hearts = 0 });
+/// ```
+///
+/// Nor do indices which are out of range
+///
+/// ```compile_fail
+/// use prime_bag::*;
+///
+/// enum Suit {
+///     Hearts,
+///     Spades,
+/// }
+///
+/// impl_prime_bag_element!(Suit { Spades = 0, Hearts = 1000 });
+/// ```
+#[macro_export]
+macro_rules! impl_prime_bag_element {
+    ($element: ty { $first_variant: ident = $first_index: literal $(, $variant: ident = $index: literal)* $(,)? }) => {
+        const _: () = {
+            let indices: &[usize] = &[$first_index $(, $index)*];
+            let mut i = 0;
+            while i < indices.len() {
+                assert!(indices[i] < $crate::NUM_PRIME_INDICES, "prime index is out of range");
+                let mut j = 0;
+                while j < i {
+                    assert!(indices[i] != indices[j], "prime indices must be unique");
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+
+        impl $crate::PrimeBagElement for $element {
+            fn to_prime_index(&self) -> usize {
+                match self {
+                    Self::$first_variant => $first_index,
+                    $(Self::$variant => $index,)*
+                }
+            }
+
+            fn from_prime_index(value: usize) -> Self {
+                match value {
+                    $first_index => Self::$first_variant,
+                    $($index => Self::$variant,)*
+                    _ => Self::$first_variant,
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PrimeBag16, PrimeBagElement};
+
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    impl_prime_bag_element!(Color {
+        Green = 0,
+        Red = 1,
+        Blue = 2,
+    });
+
+    #[test]
+    fn test_impl_prime_bag_element() {
+        for (color, index) in [(Color::Green, 0), (Color::Red, 1), (Color::Blue, 2)] {
+            assert_eq!(color.to_prime_index(), index);
+            assert_eq!(Color::from_prime_index(index), color);
+        }
+        assert_eq!(Color::from_prime_index(3), Color::Green);
+
+        let elements = [Color::Green, Color::Red, Color::Red, Color::Blue];
+        let bag = PrimeBag16::<Color>::try_from_iter(elements).unwrap();
+        assert_eq!(bag.count_instances(Color::Red), 2);
+        let actual: Vec<_> = bag.into_iter().collect();
+        assert_eq!(actual, elements);
+    }
+}
@@ -0,0 +1,160 @@
+use core::fmt::Debug;
+
+use crate::helpers::Helpers128;
+use crate::{PrimeBag128, PrimeBag16, PrimeBag32, PrimeBag64, PrimeBag8, PrimeBagElement};
+
+macro_rules! assert_bag_invariants {
+    ($fn_name: ident, $bag_x: ident) => {
+        /// Asserts that the bag satisfies the invariants this crate relies on.
+        /// This can be used to test implementations of `PrimeBagElement`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if any invariant does not hold.
+        pub fn $fn_name<E: PrimeBagElement + Eq + Debug>(bag: &$bag_x<E>) {
+            let count = bag.count();
+            assert_eq!(bag.into_iter().fold(0usize, |acc, _| acc + 1), count);
+            assert_eq!(bag.into_iter().rev().fold(0usize, |acc, _| acc + 1), count);
+            assert_eq!(bag.is_empty(), count == 0);
+            assert!(bag.is_count_at_least(count));
+            assert!(!bag.is_count_at_least(count + 1));
+
+            for element in *bag {
+                let index = element.to_prime_index();
+                assert_eq!(E::from_prime_index(index), element);
+                assert!(bag.contains(element));
+            }
+
+            assert_eq!($bag_x::try_from_iter(*bag), Some(*bag));
+
+            let mut rebuilt = $bag_x::<E>::EMPTY;
+            let mut group_total = 0usize;
+            for (element, element_count) in bag.iter_groups() {
+                group_total += element_count.get();
+                let element_count_u32 =
+                    u32::try_from(element_count.get()).expect("Count should fit in a u32");
+                let index = element.to_prime_index();
+                assert_eq!(bag.count_instances(element), element_count.get());
+                rebuilt = rebuilt
+                    .try_insert_many(E::from_prime_index(index), element_count_u32)
+                    .expect("Groups should fit in the bag");
+            }
+            assert_eq!(group_total, count);
+            assert_eq!(rebuilt, *bag);
+
+            assert!(bag.is_subset(bag));
+            assert!(bag.is_superset(bag));
+            assert!(bag.dedup().is_subset(bag));
+            assert_eq!(bag.intersection(bag), *bag);
+            assert_eq!(bag.try_union(bag), Some(*bag));
+            assert_eq!(bag.try_difference(bag), Some($bag_x::EMPTY));
+            assert_eq!($bag_x::<E>::from_inner(bag.into_inner()), *bag);
+        }
+    };
+}
+
+assert_bag_invariants!(assert_bag_invariants8, PrimeBag8);
+assert_bag_invariants!(assert_bag_invariants16, PrimeBag16);
+assert_bag_invariants!(assert_bag_invariants32, PrimeBag32);
+assert_bag_invariants!(assert_bag_invariants64, PrimeBag64);
+assert_bag_invariants!(assert_bag_invariants128, PrimeBag128);
+
+/// Asserts that the `PrimeBagElement` implementation is correct for each of the `values`.
+/// Each value must round trip through `to_prime_index` and `from_prime_index`, must have a prime index in the supported range, and distinct values must have distinct prime indices.
+///
+/// # Panics
+///
+/// Panics if the implementation is not correct for any of the values.
+pub fn assert_element_roundtrip<E: PrimeBagElement + PartialEq + Debug>(values: &[E]) {
+    for (i, value) in values.iter().enumerate() {
+        let index = value.to_prime_index();
+        assert!(
+            index < Helpers128::NUM_PRIMES,
+            "{value:?} has prime index {index} which is out of range"
+        );
+        for other in &values[..i] {
+            if other != value {
+                assert_ne!(
+                    other.to_prime_index(),
+                    index,
+                    "{other:?} and {value:?} have the same prime index"
+                );
+            }
+        }
+
+        assert_eq!(
+            &E::from_prime_index(index),
+            value,
+            "{value:?} does not round trip through prime index {index}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::random_fillings;
+
+    #[test]
+    fn test_assert_element_roundtrip() {
+        assert_element_roundtrip::<usize>(&[0, 1, 2, 3, 31, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "have the same prime index")]
+    fn test_assert_element_roundtrip_collision() {
+        #[derive(Debug, PartialEq)]
+        struct Halved(usize);
+
+        impl PrimeBagElement for Halved {
+            fn to_prime_index(&self) -> usize {
+                self.0 / 2
+            }
+
+            fn from_prime_index(value: usize) -> Self {
+                Self(value * 2)
+            }
+        }
+
+        assert_element_roundtrip(&[Halved(2), Halved(3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not round trip")]
+    fn test_assert_element_roundtrip_not_round_trip() {
+        #[derive(Debug, PartialEq)]
+        struct Offset(usize);
+
+        impl PrimeBagElement for Offset {
+            fn to_prime_index(&self) -> usize {
+                self.0
+            }
+
+            fn from_prime_index(value: usize) -> Self {
+                Self(value + 1)
+            }
+        }
+
+        assert_element_roundtrip(&[Offset(1)]);
+    }
+
+    macro_rules! test_random_bags {
+        ($test_name: ident, $fn_name: ident, $bag_x: ident) => {
+            #[test]
+            fn $test_name() {
+                for bag in random_fillings::<$bag_x<usize>>(32, false)
+                    .into_iter()
+                    .flatten()
+                {
+                    $fn_name(&bag);
+                }
+            }
+        };
+    }
+
+    test_random_bags!(test_random_bags8, assert_bag_invariants8, PrimeBag8);
+    test_random_bags!(test_random_bags16, assert_bag_invariants16, PrimeBag16);
+    test_random_bags!(test_random_bags32, assert_bag_invariants32, PrimeBag32);
+    test_random_bags!(test_random_bags64, assert_bag_invariants64, PrimeBag64);
+    test_random_bags!(test_random_bags128, assert_bag_invariants128, PrimeBag128);
+}